@@ -0,0 +1,219 @@
+use anyhow::anyhow;
+use url::form_urlencoded::byte_serialize;
+
+use crate::{destination::console_url, partition::Partition};
+
+/// An Amazon Resource Name, `arn:partition:service:region:account-id:resource`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Arn {
+    pub partition: String,
+    pub service: String,
+    pub region: String,
+    pub account_id: String,
+    pub resource: String,
+}
+
+impl Arn {
+    pub fn parse(arn: &str) -> anyhow::Result<Arn> {
+        let parts: Vec<&str> = arn.splitn(6, ':').collect();
+
+        let (partition, service, region, account_id, resource) = match parts[..] {
+            ["arn", partition, service, region, account_id, resource]
+                if !partition.is_empty() && !service.is_empty() && !resource.is_empty() =>
+            {
+                (partition, service, region, account_id, resource)
+            }
+            _ => {
+                return Err(anyhow!(
+                    "{} is not an ARN, expected arn:partition:service:region:account-id:resource",
+                    arn
+                ))
+            }
+        };
+
+        return Ok(Arn {
+            partition: partition.to_string(),
+            service: service.to_string(),
+            region: region.to_string(),
+            account_id: account_id.to_string(),
+            resource: resource.to_string(),
+        });
+    }
+
+    /// The region the resource lives in, `None` for global resources like IAM roles or S3 buckets.
+    pub fn region(&self) -> Option<&str> {
+        if self.region.is_empty() {
+            return None;
+        }
+
+        return Some(&self.region);
+    }
+
+    /// Builds the console URL of the resource page.
+    pub fn console_destination(
+        &self,
+        partition: Partition,
+        region: &str,
+    ) -> anyhow::Result<String> {
+        let arn = self.to_string();
+        let (resource_type, resource_id) = self.resource_type_and_id();
+
+        let path = match (self.service.as_str(), resource_type) {
+            ("lambda", "function") => {
+                let name = resource_id.split(':').next().unwrap_or(resource_id);
+                format!("lambda/home#/functions/{}", name)
+            }
+            // Buckets and objects are the only S3 resources without a region and account, unlike
+            // e.g. access points.
+            ("s3", _) if self.region.is_empty() && self.account_id.is_empty() => {
+                match self.resource.split_once('/') {
+                    Some((bucket, key)) => {
+                        format!("s3/object/{}?prefix={}", bucket, encode(key))
+                    }
+                    None => format!("s3/buckets/{}", self.resource),
+                }
+            }
+            ("dynamodb", "table") => {
+                let name = resource_id.split('/').next().unwrap_or(resource_id);
+                format!("dynamodbv2/home#table?name={}", name)
+            }
+            ("sqs", _) => {
+                let queue_url = format!(
+                    "https://sqs.{}.{}/{}/{}",
+                    self.region,
+                    partition.dns_suffix(),
+                    self.account_id,
+                    self.resource
+                );
+                format!("sqs/v3/home#/queues/{}", encode(&queue_url))
+            }
+            ("sns", _) => format!("sns/v3/home#/topic/{}", arn),
+            ("iam", "role") => {
+                let name = resource_id.rsplit('/').next().unwrap_or(resource_id);
+                format!("iam/home#/roles/details/{}", name)
+            }
+            ("cloudformation", "stack") => {
+                format!(
+                    "cloudformation/home#/stacks/stackinfo?stackId={}",
+                    encode(&arn)
+                )
+            }
+            ("logs", "log-group") => {
+                let name = resource_id.trim_end_matches(":*");
+                // The CloudWatch console expects the log group name URL-encoded twice, with `$` in place of `%`.
+                format!(
+                    "cloudwatch/home#logsV2:log-groups/log-group/{}",
+                    encode(name).replace('%', "$25")
+                )
+            }
+            ("states", "stateMachine") => {
+                format!("states/home#/statemachines/view/{}", encode(&arn))
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Opening {} {} resources is not supported",
+                    self.service,
+                    resource_type
+                ))
+            }
+        };
+
+        return Ok(console_url(partition, region, &path));
+    }
+
+    /// Splits `type/id` and `type:id` resources, plain resources (e.g. SQS queues) have no type.
+    fn resource_type_and_id(&self) -> (&str, &str) {
+        let separator = self.resource.find(['/', ':']);
+
+        return match separator {
+            Some(index) => (&self.resource[..index], &self.resource[index + 1..]),
+            None => ("", self.resource.as_str()),
+        };
+    }
+}
+
+impl std::fmt::Display for Arn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(
+            f,
+            "arn:{}:{}:{}:{}:{}",
+            self.partition, self.service, self.region, self.account_id, self.resource
+        );
+    }
+}
+
+fn encode(value: &str) -> String {
+    return byte_serialize(value.as_bytes())
+        .collect::<String>()
+        .replace('+', "%20");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_the_arn_parts() {
+        let arn =
+            Arn::parse("arn:aws:lambda:eu-west-1:123456789012:function:my-function:live").unwrap();
+
+        assert_eq!("aws", arn.partition);
+        assert_eq!("lambda", arn.service);
+        assert_eq!(Some("eu-west-1"), arn.region());
+        assert_eq!("123456789012", arn.account_id);
+        assert_eq!("function:my-function:live", arn.resource);
+
+        assert_eq!(
+            None,
+            Arn::parse("arn:aws:iam::1:role/admin").unwrap().region()
+        );
+        assert_eq!(true, Arn::parse("not-an-arn").is_err());
+    }
+
+    #[test]
+    fn maps_resources_to_console_pages() {
+        let destination = |arn: &str| {
+            return Arn::parse(arn)
+                .unwrap()
+                .console_destination(Partition::Aws, "eu-west-1")
+                .unwrap();
+        };
+
+        assert_eq!(
+            "https://eu-west-1.console.aws.amazon.com/lambda/home?region=eu-west-1#/functions/my-function",
+            destination("arn:aws:lambda:eu-west-1:1:function:my-function:live")
+        );
+        assert_eq!(
+            "https://eu-west-1.console.aws.amazon.com/s3/object/bucket?prefix=path%2Fto%20file.txt&region=eu-west-1",
+            destination("arn:aws:s3:::bucket/path/to file.txt")
+        );
+        assert_eq!(
+            "https://eu-west-1.console.aws.amazon.com/sqs/v3/home?region=eu-west-1#/queues/https%3A%2F%2Fsqs.eu-west-1.amazonaws.com%2F1%2Fjobs",
+            destination("arn:aws:sqs:eu-west-1:1:jobs")
+        );
+        assert_eq!(
+            "https://eu-west-1.console.aws.amazon.com/cloudwatch/home?region=eu-west-1#logsV2:log-groups/log-group/$252Faws$252Flambda$252Ffn",
+            destination("arn:aws:logs:eu-west-1:1:log-group:/aws/lambda/fn:*")
+        );
+        assert_eq!(
+            "https://eu-west-1.console.aws.amazon.com/iam/home?region=eu-west-1#/roles/details/admin",
+            destination("arn:aws:iam::1:role/team/admin")
+        );
+
+        assert_eq!(
+            true,
+            Arn::parse("arn:aws:ec2:eu-west-1:1:instance/i-1")
+                .unwrap()
+                .console_destination(Partition::Aws, "eu-west-1")
+                .is_err()
+        );
+        assert_eq!(
+            "Opening s3 accesspoint resources is not supported",
+            Arn::parse("arn:aws:s3:us-east-1:123456789012:accesspoint/reports")
+                .unwrap()
+                .console_destination(Partition::Aws, "us-east-1")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
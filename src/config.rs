@@ -0,0 +1,103 @@
+use std::{collections::HashMap, fs, io::ErrorKind, path::PathBuf};
+
+use anyhow::Context;
+
+use crate::{credentials::EnvGetter, ini::Ini};
+
+pub type Profile = HashMap<String, String>;
+
+/// Resolves the `~/.aws` directory.
+pub fn aws_dir(env_getter: &EnvGetter) -> anyhow::Result<PathBuf> {
+    let home = env_getter("HOME")
+        .or_else(|_| env_getter("USERPROFILE"))
+        .context("Could not determine the home directory")?;
+
+    return Ok(PathBuf::from(home).join(".aws"));
+}
+
+/// Resolves a file living in the `~/.aws` directory, honouring `env_key` as an override.
+pub fn aws_file_path(
+    env_getter: &EnvGetter,
+    env_key: &str,
+    file_name: &str,
+) -> anyhow::Result<PathBuf> {
+    if let Ok(path) = env_getter(env_key) {
+        return Ok(PathBuf::from(path));
+    }
+
+    return Ok(aws_dir(env_getter)?.join(file_name));
+}
+
+/// Reads and parses an INI file, returning `None` when the file does not exist.
+pub fn read_ini_file(path: &PathBuf) -> anyhow::Result<Option<Ini>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(error) => {
+            return Err(error).with_context(|| format!("Could not read {}", path.display()))
+        }
+    };
+
+    let ini =
+        Ini::parse(&contents).with_context(|| format!("Could not parse {}", path.display()))?;
+
+    return Ok(Some(ini));
+}
+
+/// Loads `~/.aws/config` (or `AWS_CONFIG_FILE`), yielding an empty config when the file is missing.
+pub fn load_config(env_getter: &EnvGetter) -> anyhow::Result<Ini> {
+    let path = aws_file_path(env_getter, "AWS_CONFIG_FILE", "config")?;
+
+    return Ok(read_ini_file(&path)?.unwrap_or_default());
+}
+
+/// Looks up a profile in the config file, where every profile but `default` lives under `[profile name]`.
+pub fn profile<'a>(config: &'a Ini, profile_name: &str) -> Option<&'a Profile> {
+    let section = config.section(&format!("profile {}", profile_name));
+
+    if profile_name == "default" {
+        return section.or_else(|| config.section("default"));
+    }
+
+    return section;
+}
+
+/// A setting given with a flag, falling back to the `key` of the profile, e.g. `--issuer` and `console_issuer`.
+pub fn profile_setting<'a>(
+    flag: Option<&'a str>,
+    config_profile: Option<&'a Profile>,
+    key: &str,
+) -> Option<&'a str> {
+    return flag.or_else(|| {
+        config_profile
+            .and_then(|config_profile| config_profile.get(key))
+            .map(String::as_str)
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn prefers_the_flag_over_the_profile() {
+        let config_profile = Profile::from([(
+            String::from("console_browser"),
+            String::from("firefox -P {profile}"),
+        )]);
+
+        assert_eq!(
+            Some("chromium"),
+            profile_setting(Some("chromium"), Some(&config_profile), "console_browser")
+        );
+        assert_eq!(
+            Some("firefox -P {profile}"),
+            profile_setting(None, Some(&config_profile), "console_browser")
+        );
+        assert_eq!(
+            None,
+            profile_setting(None, Some(&config_profile), "console_issuer")
+        );
+        assert_eq!(None, profile_setting(None, None, "console_browser"));
+    }
+}
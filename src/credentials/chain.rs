@@ -0,0 +1,310 @@
+use anyhow::{anyhow, Context};
+use clap::ArgEnum;
+
+use super::{
+    config_profile::ConfigProfileProvider,
+    container::ContainerProvider,
+    env::EnvProvider,
+    imds::ImdsProvider,
+    mfa::MfaTokenProvider,
+    process::ProcessProvider,
+    shared_file::{load_shared_credentials, SharedFileProvider},
+    sso::SsoProvider,
+    web_identity::WebIdentityProvider,
+    Credentials, EnvGetter,
+};
+use crate::{
+    config::{load_config, profile},
+    sso::SsoClient,
+    sts::StsClient,
+};
+
+/// Everything a provider may need to resolve credentials.
+pub struct ProviderContext<'a> {
+    pub env_getter: &'a EnvGetter,
+    pub mfa_token_provider: &'a MfaTokenProvider,
+    pub sts: &'a StsClient,
+    pub sso: &'a SsoClient,
+    /// The requested console session length, which rules out assuming a role with another role's credentials.
+    pub session_duration: Option<u32>,
+}
+
+pub trait CredentialProvider {
+    /// The name reported in verbose output, matching the `--credential-source` value.
+    fn name(&self) -> &'static str;
+
+    /// Resolves the credentials of `profile_name`, or `None` when the source is not configured for it.
+    fn provide(
+        &self,
+        profile_name: &str,
+        context: &ProviderContext,
+    ) -> anyhow::Result<Option<Credentials>>;
+
+    /// Whether the source ignores the profile and uses the credentials of the environment the tool runs in.
+    fn is_profile_agnostic(&self) -> bool {
+        return false;
+    }
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CredentialSource {
+    Env,
+    ConfigProfile,
+    Sso,
+    SharedFile,
+    Process,
+    WebIdentity,
+    Container,
+    Imds,
+}
+
+impl CredentialSource {
+    /// The order mirrors the precedence the AWS CLI gives to the settings of a profile,
+    /// falling back to the credentials of the environment the tool runs in.
+    pub const DEFAULT_CHAIN: &'static [CredentialSource] = &[
+        CredentialSource::Env,
+        CredentialSource::ConfigProfile,
+        CredentialSource::Sso,
+        CredentialSource::SharedFile,
+        CredentialSource::Process,
+        CredentialSource::WebIdentity,
+        CredentialSource::Container,
+        CredentialSource::Imds,
+    ];
+
+    pub fn provider(self) -> Box<dyn CredentialProvider> {
+        return match self {
+            CredentialSource::Env => Box::new(EnvProvider),
+            CredentialSource::ConfigProfile => Box::new(ConfigProfileProvider),
+            CredentialSource::Sso => Box::new(SsoProvider),
+            CredentialSource::SharedFile => Box::new(SharedFileProvider),
+            CredentialSource::Process => Box::new(ProcessProvider),
+            CredentialSource::WebIdentity => Box::new(WebIdentityProvider),
+            CredentialSource::Container => Box::new(ContainerProvider),
+            CredentialSource::Imds => Box::new(ImdsProvider),
+        };
+    }
+}
+
+/// Asks every provider in turn, returning the credentials of the first one configured for the profile.
+///
+/// A provider that is configured but fails stops the chain, so that e.g. an expired SSO token
+/// is reported instead of silently falling through to the next source. Like the AWS CLI, the
+/// profile-agnostic sources are only consulted for `default` or a profile of the shared files, so that
+/// a mistyped profile name does not open the console as the role of the machine.
+pub fn resolve_credentials(
+    profile_name: &str,
+    providers: &[Box<dyn CredentialProvider>],
+    context: &ProviderContext,
+    verbose: bool,
+) -> anyhow::Result<Credentials> {
+    let mut is_known_profile = None;
+
+    for provider in providers {
+        if provider.is_profile_agnostic() {
+            let is_known = match is_known_profile {
+                Some(is_known) => is_known,
+                None => *is_known_profile.insert(profile_exists(profile_name, context)?),
+            };
+
+            if !is_known {
+                if verbose {
+                    eprintln!(
+                        "Skipping the {} source, profile {} does not exist",
+                        provider.name(),
+                        profile_name
+                    );
+                }
+
+                continue;
+            }
+        }
+
+        let credentials = provider
+            .provide(profile_name, context)
+            .with_context(|| format!("The {} credential source failed", provider.name()))?;
+
+        match credentials {
+            Some(credentials) => {
+                if verbose {
+                    eprintln!("Using credentials from the {} source", provider.name());
+                }
+
+                return Ok(credentials);
+            }
+            None => {
+                if verbose {
+                    eprintln!(
+                        "The {} source is not configured for profile {}",
+                        provider.name(),
+                        profile_name
+                    );
+                }
+            }
+        }
+    }
+
+    let names = providers
+        .iter()
+        .map(|provider| provider.name())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    return Err(anyhow!(
+        "No credentials found for profile {} (tried {})",
+        profile_name,
+        names
+    ));
+}
+
+/// Whether the profile is `default` or has a section in the config or shared credentials file.
+fn profile_exists(profile_name: &str, context: &ProviderContext) -> anyhow::Result<bool> {
+    if profile_name == "default" {
+        return Ok(true);
+    }
+
+    if profile(&load_config(context.env_getter)?, profile_name).is_some() {
+        return Ok(true);
+    }
+
+    let shared_credentials = load_shared_credentials(context.env_getter)?.unwrap_or_default();
+
+    return Ok(shared_credentials.section(profile_name).is_some());
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use super::*;
+    use crate::test_server::serve_once;
+
+    struct FakeProvider {
+        name: &'static str,
+        credentials: Option<&'static str>,
+    }
+
+    impl CredentialProvider for FakeProvider {
+        fn name(&self) -> &'static str {
+            return self.name;
+        }
+
+        fn provide(
+            &self,
+            _profile_name: &str,
+            _context: &ProviderContext,
+        ) -> anyhow::Result<Option<Credentials>> {
+            return Ok(self.credentials.map(|access_key_id| Credentials {
+                access_key_id: String::from(access_key_id),
+                secret_access_key: String::from("secret"),
+                session_token: None,
+                expiration: None,
+            }));
+        }
+    }
+
+    #[test]
+    fn first_configured_provider_wins() {
+        let sts = StsClient::new(None, "eu-west-1");
+        let sso = SsoClient::new(None);
+        let context = ProviderContext {
+            env_getter: &|_| Err(anyhow!("not set")),
+            mfa_token_provider: &|_| Err(anyhow!("no MFA")),
+            sts: &sts,
+            sso: &sso,
+            session_duration: None,
+        };
+
+        let providers: Vec<Box<dyn CredentialProvider>> = vec![
+            Box::new(FakeProvider {
+                name: "first",
+                credentials: None,
+            }),
+            Box::new(FakeProvider {
+                name: "second",
+                credentials: Some("SECOND"),
+            }),
+            Box::new(FakeProvider {
+                name: "third",
+                credentials: Some("THIRD"),
+            }),
+        ];
+
+        let credentials = resolve_credentials("dev", &providers, &context, false).unwrap();
+        assert_eq!("SECOND", credentials.access_key_id);
+
+        let error_message = format!(
+            "{}",
+            resolve_credentials("dev", &providers[..1], &context, false)
+                .err()
+                .unwrap()
+        );
+        assert_eq!(
+            "No credentials found for profile dev (tried first)",
+            error_message
+        );
+    }
+
+    #[test]
+    fn skips_the_environment_for_unknown_profiles() {
+        let token_file = env::temp_dir().join(format!(
+            "aws-console-link-chain-token-{}",
+            std::process::id()
+        ));
+        fs::write(&token_file, "oidc_token").unwrap();
+        let token_path = token_file.to_string_lossy().to_string();
+
+        let env_getter = move |key: &str| {
+            return match key {
+                "AWS_CONFIG_FILE" | "AWS_SHARED_CREDENTIALS_FILE" => {
+                    Ok(String::from("/nonexistent/aws-console-link"))
+                }
+                "AWS_WEB_IDENTITY_TOKEN_FILE" => Ok(token_path.clone()),
+                "AWS_ROLE_ARN" => Ok(String::from("arn:aws:iam::1:role/pod")),
+                _ => Err(anyhow!("not set")),
+            };
+        };
+
+        let (endpoint, request) = serve_once(
+            200,
+            "<AssumeRoleWithWebIdentityResponse><AssumeRoleWithWebIdentityResult><Credentials><AccessKeyId>ASIA</AccessKeyId><SecretAccessKey>secret</SecretAccessKey><SessionToken>token</SessionToken></Credentials></AssumeRoleWithWebIdentityResult></AssumeRoleWithWebIdentityResponse>",
+        );
+        let sts = StsClient::new(Some(endpoint), "eu-west-1");
+        let sso = SsoClient::new(None);
+        let context = ProviderContext {
+            env_getter: &env_getter,
+            mfa_token_provider: &|_| Err(anyhow!("no MFA")),
+            sts: &sts,
+            sso: &sso,
+            session_duration: None,
+        };
+
+        let providers: Vec<_> = CredentialSource::DEFAULT_CHAIN
+            .iter()
+            .map(|source| source.provider())
+            .collect();
+
+        let error_message = format!(
+            "{}",
+            resolve_credentials("prdo", &providers, &context, false)
+                .err()
+                .unwrap()
+        );
+        assert_eq!(
+            true,
+            error_message.starts_with("No credentials found for profile prdo")
+        );
+
+        let credentials = resolve_credentials("default", &providers, &context, false).unwrap();
+        assert_eq!("ASIA", credentials.access_key_id);
+        assert_eq!(
+            true,
+            request
+                .join()
+                .unwrap()
+                .contains("Action=AssumeRoleWithWebIdentity")
+        );
+
+        fs::remove_file(token_file).unwrap();
+    }
+}
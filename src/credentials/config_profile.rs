@@ -0,0 +1,368 @@
+use anyhow::{anyhow, Context};
+use chrono::Utc;
+
+use super::{
+    process::{get_process_credentials, process_command},
+    shared_file::{load_shared_credentials, static_credentials, static_section},
+    sso::{get_sso_credentials, is_sso_profile},
+    web_identity::get_web_identity_credentials,
+    CredentialProvider, Credentials, ProviderContext,
+};
+use crate::{
+    config::{load_config, profile, Profile},
+    ini::Ini,
+    sts::AssumeRoleRequest,
+};
+
+/// Resolves `~/.aws/config` profiles with a `role_arn`, following their `source_profile` chain
+/// and assuming every role along the way, or exchanging their `web_identity_token_file`.
+pub struct ConfigProfileProvider;
+
+impl CredentialProvider for ConfigProfileProvider {
+    fn name(&self) -> &'static str {
+        return "config-profile";
+    }
+
+    fn provide(
+        &self,
+        profile_name: &str,
+        context: &ProviderContext,
+    ) -> anyhow::Result<Option<Credentials>> {
+        let config = load_config(context.env_getter)?;
+        let has_role = profile(&config, profile_name)
+            .is_some_and(|config_profile| config_profile.contains_key("role_arn"));
+        if !has_role {
+            return Ok(None);
+        }
+
+        // Checked before any role is assumed, so that the MFA code is not asked for in vain.
+        if context.session_duration.is_some() && is_role_chain(&config, profile_name) {
+            return Err(anyhow!(
+                "Profile {} assumes a role with the credentials of another role, AWS does not allow a session duration for role chaining",
+                profile_name
+            ));
+        }
+
+        let shared_credentials = load_shared_credentials(context.env_getter)?.unwrap_or_default();
+
+        let resolver = ProfileResolver {
+            config: &config,
+            shared_credentials: &shared_credentials,
+            context,
+        };
+
+        return resolver.resolve(profile_name, &mut vec![]).map(Some);
+    }
+}
+
+/// Whether assuming the role of the profile uses the credentials of another role, as with a
+/// `source_profile` that assumes a role itself or signs in with IAM Identity Center.
+fn is_role_chain(config: &Ini, profile_name: &str) -> bool {
+    let source_profile = profile(config, profile_name)
+        .filter(|config_profile| config_profile.contains_key("role_arn"))
+        .and_then(|config_profile| config_profile.get("source_profile"))
+        .filter(|source_profile| *source_profile != profile_name)
+        .and_then(|source_profile| profile(config, source_profile));
+
+    return source_profile.is_some_and(|source_profile| {
+        source_profile.contains_key("role_arn") || is_sso_profile(source_profile)
+    });
+}
+
+struct ProfileResolver<'a> {
+    config: &'a Ini,
+    shared_credentials: &'a Ini,
+    context: &'a ProviderContext<'a>,
+}
+
+impl ProfileResolver<'_> {
+    fn resolve(
+        &self,
+        profile_name: &str,
+        visited: &mut Vec<String>,
+    ) -> anyhow::Result<Credentials> {
+        if visited.iter().any(|name| name == profile_name) {
+            return Err(anyhow!(
+                "The source_profile chain loops back to profile {}",
+                profile_name
+            ));
+        }
+        visited.push(profile_name.to_string());
+
+        let config_profile = profile(self.config, profile_name);
+        let role_arn = config_profile.and_then(|profile| profile.get("role_arn"));
+
+        let (config_profile, role_arn) = match (config_profile, role_arn) {
+            (Some(config_profile), Some(role_arn)) => (config_profile, role_arn),
+            _ => return self.profile_credentials(profile_name, config_profile),
+        };
+
+        let default_session_name = format!("aws-console-link-{}", Utc::now().timestamp());
+        let role_session_name = config_profile
+            .get("role_session_name")
+            .unwrap_or(&default_session_name);
+
+        if let Some(token_file) = config_profile.get("web_identity_token_file") {
+            return get_web_identity_credentials(
+                token_file,
+                role_arn,
+                role_session_name,
+                self.context.sts,
+            );
+        }
+
+        let source_profile = config_profile.get("source_profile").ok_or_else(|| {
+            anyhow!(
+                "Profile {} has a role_arn but neither a source_profile nor a web_identity_token_file",
+                profile_name
+            )
+        })?;
+
+        // A profile may use itself as the source, in which case its static keys sign the call.
+        let source_credentials = if source_profile == profile_name {
+            let section = static_section(self.shared_credentials, self.config, profile_name)
+                .unwrap_or(config_profile);
+            static_credentials(section, profile_name)?
+        } else {
+            self.resolve(source_profile, visited)
+                .with_context(|| format!("Could not resolve source profile {}", source_profile))?
+        };
+
+        let mfa_serial = config_profile.get("mfa_serial");
+        let mfa_code = mfa_serial
+            .map(|serial_number| (self.context.mfa_token_provider)(serial_number))
+            .transpose()?;
+
+        return self.context.sts.assume_role(
+            &source_credentials,
+            &AssumeRoleRequest {
+                role_arn,
+                role_session_name,
+                external_id: config_profile.get("external_id").map(String::as_str),
+                duration_seconds: config_profile.get("duration_seconds").map(String::as_str),
+                mfa: mfa_serial.map(String::as_str).zip(mfa_code.as_deref()),
+            },
+        );
+    }
+
+    /// Resolves a profile that does not assume a role, preferring IAM Identity Center, then the
+    /// shared credentials file, then `credential_process`, then static keys in the config file.
+    fn profile_credentials(
+        &self,
+        profile_name: &str,
+        config_profile: Option<&Profile>,
+    ) -> anyhow::Result<Credentials> {
+        if let Some(config_profile) = config_profile {
+            if is_sso_profile(config_profile) {
+                return get_sso_credentials(
+                    profile_name,
+                    config_profile,
+                    self.config,
+                    self.context.env_getter,
+                    self.context.sso,
+                );
+            }
+        }
+
+        if let Some(section) = self
+            .shared_credentials
+            .section(profile_name)
+            .filter(|section| section.contains_key("aws_access_key_id"))
+        {
+            return static_credentials(section, profile_name);
+        }
+
+        if let Some(command) = process_command(self.shared_credentials, self.config, profile_name) {
+            return get_process_credentials(command);
+        }
+
+        let config_profile = config_profile
+            .ok_or_else(|| anyhow!("No credentials found for profile {}", profile_name))?;
+
+        return static_credentials(config_profile, profile_name);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs};
+
+    use super::*;
+    use crate::{
+        sso::SsoClient,
+        sts::StsClient,
+        test_server::{serve, serve_once},
+    };
+
+    fn test_context<'a>(sts: &'a StsClient, sso: &'a SsoClient) -> ProviderContext<'a> {
+        return ProviderContext {
+            env_getter: &|_| Err(anyhow!("not set")),
+            mfa_token_provider: &|_| Ok(String::from("123456")),
+            sts,
+            sso,
+            session_duration: None,
+        };
+    }
+
+    const ASSUME_ROLE_RESPONSE: &str = "<AssumeRoleResponse><AssumeRoleResult><Credentials><AccessKeyId>ASIA</AccessKeyId><SecretAccessKey>secret</SecretAccessKey><SessionToken>token</SessionToken></Credentials></AssumeRoleResult></AssumeRoleResponse>";
+
+    #[test]
+    fn walks_the_source_profile_chain() {
+        let config = Ini::parse(
+            "[profile admin]\nrole_arn = arn:aws:iam::1:role/admin\nsource_profile = jump\n\n[profile jump]\nrole_arn = arn:aws:iam::1:role/jump\nsource_profile = base\n",
+        )
+        .unwrap();
+        let shared_credentials =
+            Ini::parse("[base]\naws_access_key_id = AKIA\naws_secret_access_key = base_secret\n")
+                .unwrap();
+
+        let (endpoint, requests) = serve(vec![
+            (200, ASSUME_ROLE_RESPONSE),
+            (200, ASSUME_ROLE_RESPONSE),
+        ]);
+        let sts = StsClient::new(Some(endpoint), "eu-west-1");
+
+        assert_eq!(true, is_role_chain(&config, "admin"));
+        assert_eq!(false, is_role_chain(&config, "jump"));
+
+        let sso = SsoClient::new(None);
+        let context = test_context(&sts, &sso);
+        let resolver = ProfileResolver {
+            config: &config,
+            shared_credentials: &shared_credentials,
+            context: &context,
+        };
+
+        let credentials = resolver.resolve("admin", &mut vec![]).unwrap();
+        assert_eq!("ASIA", credentials.access_key_id);
+
+        let requests = requests.join().unwrap();
+        assert_eq!(true, requests[0].contains("Credential=AKIA/"));
+        assert_eq!(true, requests[0].contains("role%2Fjump"));
+        assert_eq!(true, requests[1].contains("Credential=ASIA/"));
+        assert_eq!(true, requests[1].contains("role%2Fadmin"));
+    }
+
+    #[test]
+    fn passes_the_mfa_code() {
+        let config = Ini::parse(
+            "[profile admin]\nrole_arn = arn:aws:iam::1:role/admin\nsource_profile = base\nmfa_serial = arn:aws:iam::1:mfa/user\n",
+        )
+        .unwrap();
+        let shared_credentials =
+            Ini::parse("[base]\naws_access_key_id = AKIA\naws_secret_access_key = base_secret\n")
+                .unwrap();
+
+        let (endpoint, request) = serve_once(200, ASSUME_ROLE_RESPONSE);
+        let sts = StsClient::new(Some(endpoint), "eu-west-1");
+        let sso = SsoClient::new(None);
+        let context = test_context(&sts, &sso);
+        let resolver = ProfileResolver {
+            config: &config,
+            shared_credentials: &shared_credentials,
+            context: &context,
+        };
+
+        resolver.resolve("admin", &mut vec![]).unwrap();
+
+        let request = request.join().unwrap();
+        assert_eq!(
+            true,
+            request.contains("SerialNumber=arn%3Aaws%3Aiam%3A%3A1%3Amfa%2Fuser")
+        );
+        assert_eq!(true, request.contains("TokenCode=123456"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn runs_the_credential_process_of_the_credentials_file() {
+        let config = Ini::parse(
+            "[profile admin]\nrole_arn = arn:aws:iam::1:role/admin\nsource_profile = base\n",
+        )
+        .unwrap();
+        let shared_credentials = Ini::parse(
+            "[base]\ncredential_process = echo '{\"Version\": 1, \"AccessKeyId\": \"AKIA\", \"SecretAccessKey\": \"base_secret\"}'\n",
+        )
+        .unwrap();
+
+        let (endpoint, request) = serve_once(200, ASSUME_ROLE_RESPONSE);
+        let sts = StsClient::new(Some(endpoint), "eu-west-1");
+        let sso = SsoClient::new(None);
+        let context = test_context(&sts, &sso);
+        let resolver = ProfileResolver {
+            config: &config,
+            shared_credentials: &shared_credentials,
+            context: &context,
+        };
+
+        resolver.resolve("admin", &mut vec![]).unwrap();
+
+        let request = request.join().unwrap();
+        assert_eq!(true, request.contains("Credential=AKIA/"));
+    }
+
+    #[test]
+    fn rejects_a_session_duration_before_assuming_chained_roles() {
+        let config_file = env::temp_dir().join(format!(
+            "aws-console-link-role-chain-{}",
+            std::process::id()
+        ));
+        fs::write(
+            &config_file,
+            "[profile admin]\nrole_arn = arn:aws:iam::1:role/admin\nsource_profile = jump\nmfa_serial = arn:aws:iam::1:mfa/user\n\n[profile jump]\nrole_arn = arn:aws:iam::1:role/jump\nsource_profile = base\n",
+        )
+        .unwrap();
+        let config_path = config_file.to_string_lossy().to_string();
+
+        let sts = StsClient::new(Some(String::from("http://127.0.0.1:1/")), "eu-west-1");
+        let sso = SsoClient::new(None);
+        let context = ProviderContext {
+            env_getter: &move |key| {
+                return match key {
+                    "AWS_CONFIG_FILE" => Ok(config_path.clone()),
+                    "AWS_SHARED_CREDENTIALS_FILE" => {
+                        Ok(String::from("/nonexistent/aws-console-link"))
+                    }
+                    _ => Err(anyhow!("not set")),
+                };
+            },
+            mfa_token_provider: &|_| panic!("prompted for the MFA code"),
+            sts: &sts,
+            sso: &sso,
+            session_duration: Some(3600),
+        };
+
+        let result = ConfigProfileProvider.provide("admin", &context);
+
+        let error_message = format!("{:#}", result.err().unwrap());
+        assert_eq!(
+            true,
+            error_message.contains("AWS does not allow a session duration for role chaining")
+        );
+
+        fs::remove_file(config_file).unwrap();
+    }
+
+    #[test]
+    fn rejects_source_profile_cycles() {
+        let config = Ini::parse(
+            "[profile a]\nrole_arn = arn:aws:iam::1:role/a\nsource_profile = b\n\n[profile b]\nrole_arn = arn:aws:iam::1:role/b\nsource_profile = a\n",
+        )
+        .unwrap();
+        let sts = StsClient::new(Some(String::from("http://127.0.0.1:1/")), "eu-west-1");
+
+        let shared_credentials = Ini::default();
+        let sso = SsoClient::new(None);
+        let context = test_context(&sts, &sso);
+        let resolver = ProfileResolver {
+            config: &config,
+            shared_credentials: &shared_credentials,
+            context: &context,
+        };
+
+        let result = resolver.resolve("a", &mut vec![]);
+
+        let error_message = format!("{:#}", result.err().unwrap());
+        assert_eq!(true, error_message.contains("loops back to profile a"));
+    }
+}
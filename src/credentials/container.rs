@@ -0,0 +1,167 @@
+use std::{fs, time::Duration};
+
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+use url::{Host, Url};
+
+use super::{parse_expiration, CredentialProvider, Credentials, EnvGetter, ProviderContext};
+
+const ECS_CREDENTIALS_HOST: &str = "http://169.254.170.2";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ContainerCredentialsResponse {
+    access_key_id: String,
+    secret_access_key: String,
+    token: String,
+    expiration: Option<String>,
+}
+
+/// Fetches the task role credentials from the ECS/EKS container credentials endpoint.
+pub struct ContainerProvider;
+
+impl CredentialProvider for ContainerProvider {
+    fn name(&self) -> &'static str {
+        return "container";
+    }
+
+    fn is_profile_agnostic(&self) -> bool {
+        return true;
+    }
+
+    fn provide(
+        &self,
+        _profile_name: &str,
+        context: &ProviderContext,
+    ) -> anyhow::Result<Option<Credentials>> {
+        let env_getter = context.env_getter;
+
+        let endpoint = match (
+            env_getter("AWS_CONTAINER_CREDENTIALS_RELATIVE_URI"),
+            env_getter("AWS_CONTAINER_CREDENTIALS_FULL_URI"),
+        ) {
+            (Ok(relative_uri), _) => format!("{}{}", ECS_CREDENTIALS_HOST, relative_uri),
+            (_, Ok(full_uri)) => full_uri,
+            _ => return Ok(None),
+        };
+
+        return get_container_credentials(&endpoint, env_getter).map(Some);
+    }
+}
+
+fn get_container_credentials(
+    endpoint: &str,
+    env_getter: &EnvGetter,
+) -> anyhow::Result<Credentials> {
+    let url = Url::parse(endpoint)
+        .with_context(|| format!("Invalid container credentials endpoint {}", endpoint))?;
+
+    if url.scheme() != "https" && !is_allowed_http_host(&url) {
+        return Err(anyhow!(
+            "The container credentials endpoint {} must use HTTPS or a loopback/container host",
+            endpoint
+        ));
+    }
+
+    let authorization_token = match env_getter("AWS_CONTAINER_AUTHORIZATION_TOKEN_FILE") {
+        Ok(path) => Some(
+            fs::read_to_string(&path)
+                .with_context(|| format!("Could not read the authorization token at {}", path))?
+                .trim()
+                .to_string(),
+        ),
+        Err(_) => env_getter("AWS_CONTAINER_AUTHORIZATION_TOKEN").ok(),
+    };
+
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(5))
+        .build()
+        .context("Could not create the HTTP client")?;
+
+    let mut request = client.get(url);
+    if let Some(authorization_token) = authorization_token {
+        request = request.header("Authorization", authorization_token);
+    }
+
+    let res = request
+        .send()
+        .context("The container credentials request failed")?;
+
+    let status = res.status();
+    if !status.is_success() {
+        let body = res.text().unwrap_or_default();
+        return Err(anyhow!(
+            "The container credentials endpoint responded with {}: {}",
+            status,
+            body
+        ));
+    }
+
+    let body = res
+        .json::<ContainerCredentialsResponse>()
+        .context("Failed to deserialize the container credentials")?;
+
+    let expiration = parse_expiration(body.expiration.as_deref())?;
+
+    return Ok(Credentials {
+        access_key_id: body.access_key_id,
+        secret_access_key: body.secret_access_key,
+        session_token: Some(body.token),
+        expiration,
+    });
+}
+
+/// Plain HTTP is only trusted for loopback addresses and the ECS/EKS link-local agents.
+fn is_allowed_http_host(url: &Url) -> bool {
+    return match url.host() {
+        Some(Host::Domain(domain)) => domain == "localhost",
+        Some(Host::Ipv4(ip)) => {
+            ip.is_loopback()
+                || ip.octets() == [169, 254, 170, 2]
+                || ip.octets() == [169, 254, 170, 23]
+        }
+        Some(Host::Ipv6(ip)) => {
+            ip.is_loopback() || ip.segments() == [0xfd00, 0x0ec2, 0, 0, 0, 0, 0, 0x23]
+        }
+        None => false,
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_server::serve_once;
+
+    #[test]
+    fn fetches_credentials_with_the_authorization_token() {
+        let (endpoint, request) = serve_once(
+            200,
+            r#"{"AccessKeyId": "ASIA", "SecretAccessKey": "secret", "Token": "token", "Expiration": "2999-01-01T00:00:00Z"}"#,
+        );
+
+        let env_getter: Box<EnvGetter> = Box::new(|key| {
+            if key == "AWS_CONTAINER_AUTHORIZATION_TOKEN" {
+                return Ok(String::from("auth_token"));
+            }
+
+            return Err(anyhow!("not set"));
+        });
+
+        let credentials =
+            get_container_credentials(&format!("{}v2/credentials", endpoint), &env_getter).unwrap();
+        assert_eq!("ASIA", credentials.access_key_id);
+        assert_eq!(Some(String::from("token")), credentials.session_token);
+
+        let request = request.join().unwrap();
+        assert_eq!(true, request.starts_with("GET /v2/credentials "));
+        assert_eq!(true, request.contains("authorization: auth_token"));
+    }
+
+    #[test]
+    fn rejects_plain_http_to_remote_hosts() {
+        let env_getter: Box<EnvGetter> = Box::new(|_| Err(anyhow!("not set")));
+
+        let result = get_container_credentials("http://example.com/credentials", &env_getter);
+        assert_eq!(true, result.is_err());
+    }
+}
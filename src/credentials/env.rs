@@ -0,0 +1,69 @@
+use anyhow::{anyhow, Context};
+
+use super::{CredentialProvider, Credentials, EnvGetter, ProviderContext};
+
+pub fn get_aws_credentials(
+    profile_name: &str,
+    env_getter: &EnvGetter,
+) -> anyhow::Result<Credentials> {
+    let exported_profile_name =
+        env_getter("AWS_PROFILE").context("Missing AWS_PROFILE variable")?;
+
+    if profile_name != exported_profile_name {
+        return Err(anyhow!(
+            "Request profile name different than the exported profile name"
+        ));
+    }
+
+    let access_key_id =
+        env_getter("AWS_ACCESS_KEY_ID").context("Missing AWS_ACCESS_KEY_ID variable")?;
+
+    let secret_access_key =
+        env_getter("AWS_SECRET_ACCESS_KEY").context("Missing AWS_SECRET_ACCESS_KEY variable")?;
+
+    return Ok(Credentials {
+        access_key_id,
+        secret_access_key,
+        session_token: env_getter("AWS_SESSION_TOKEN").ok(),
+        expiration: None,
+    });
+}
+
+/// Uses the exported `AWS_*` variables when `AWS_PROFILE` matches the requested profile.
+pub struct EnvProvider;
+
+impl CredentialProvider for EnvProvider {
+    fn name(&self) -> &'static str {
+        return "env";
+    }
+
+    fn provide(
+        &self,
+        profile_name: &str,
+        context: &ProviderContext,
+    ) -> anyhow::Result<Option<Credentials>> {
+        return Ok(get_aws_credentials(profile_name, context.env_getter).ok());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_aws_profile() {
+        let env_getter: Box<EnvGetter> = Box::new(|key| {
+            if key == "AWS_PROFILE" {
+                return Err(anyhow!("test_error"));
+            }
+
+            return Ok(String::from("foo"));
+        });
+
+        let result = get_aws_credentials("test_profile", &env_getter);
+        assert_eq!(true, result.is_err());
+
+        let error_message = format!("{}", result.err().unwrap().source().unwrap());
+        assert_eq!("test_error", error_message)
+    }
+}
@@ -0,0 +1,73 @@
+use std::{fs, path::Path};
+
+use anyhow::Context;
+
+use super::Credentials;
+use crate::sts::{GetFederationTokenRequest, StsClient};
+
+/// The federated user's permissions are the intersection of the IAM user's policies and this
+/// policy, so by default the console session gets everything the IAM user is allowed to do.
+const ALLOW_ALL_POLICY: &str =
+    r#"{"Version":"2012-10-17","Statement":[{"Effect":"Allow","Action":"*","Resource":"*"}]}"#;
+
+const FEDERATED_USER_NAME: &str = "aws-console-link";
+
+/// Exchanges long-term IAM user keys for session credentials the federation endpoint accepts.
+pub fn get_federated_credentials(
+    credentials: &Credentials,
+    policy_path: Option<&Path>,
+    duration_seconds: Option<u32>,
+    sts: &StsClient,
+) -> anyhow::Result<Credentials> {
+    let policy = match policy_path {
+        Some(path) => fs::read_to_string(path).with_context(|| {
+            format!("Could not read the federation policy at {}", path.display())
+        })?,
+        None => String::from(ALLOW_ALL_POLICY),
+    };
+    let duration_seconds = duration_seconds.map(|duration| duration.to_string());
+
+    return sts.get_federation_token(
+        credentials,
+        &GetFederationTokenRequest {
+            name: FEDERATED_USER_NAME,
+            policy: Some(&policy),
+            duration_seconds: duration_seconds.as_deref(),
+        },
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_server::serve_once;
+
+    #[test]
+    fn passes_the_policy_and_duration() {
+        let (endpoint, request) = serve_once(
+            200,
+            "<GetFederationTokenResponse><GetFederationTokenResult><Credentials><AccessKeyId>ASIA</AccessKeyId><SecretAccessKey>secret</SecretAccessKey><SessionToken>token</SessionToken></Credentials></GetFederationTokenResult></GetFederationTokenResponse>",
+        );
+
+        let long_term = Credentials {
+            access_key_id: String::from("AKIA"),
+            secret_access_key: String::from("secret"),
+            session_token: None,
+            expiration: None,
+        };
+        let credentials = get_federated_credentials(
+            &long_term,
+            None,
+            Some(3600),
+            &StsClient::new(Some(endpoint), "eu-west-1"),
+        )
+        .unwrap();
+        assert_eq!(Some(String::from("token")), credentials.session_token);
+
+        let request = request.join().unwrap();
+        assert_eq!(true, request.contains("Action=GetFederationToken"));
+        assert_eq!(true, request.contains("Name=aws-console-link"));
+        assert_eq!(true, request.contains("Policy=%7B%22Version%22"));
+        assert_eq!(true, request.contains("DurationSeconds=3600"));
+    }
+}
@@ -0,0 +1,181 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Context};
+use reqwest::blocking::{Client, RequestBuilder};
+use serde::Deserialize;
+
+use super::{parse_expiration, CredentialProvider, Credentials, EnvGetter, ProviderContext};
+
+const DEFAULT_ENDPOINT: &str = "http://169.254.169.254";
+const TOKEN_TTL_SECONDS: &str = "21600";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InstanceCredentialsResponse {
+    code: String,
+    access_key_id: String,
+    secret_access_key: String,
+    token: String,
+    expiration: String,
+}
+
+/// Uses the instance role credentials served by the EC2 instance metadata service (IMDSv2).
+///
+/// The endpoint can be overridden with `AWS_EC2_METADATA_SERVICE_ENDPOINT` and the per-request timeout,
+/// one second by default, with `AWS_METADATA_SERVICE_TIMEOUT`. Setting `AWS_EC2_METADATA_DISABLED=true`
+/// skips the provider.
+pub struct ImdsProvider;
+
+impl CredentialProvider for ImdsProvider {
+    fn name(&self) -> &'static str {
+        return "imds";
+    }
+
+    fn is_profile_agnostic(&self) -> bool {
+        return true;
+    }
+
+    fn provide(
+        &self,
+        _profile_name: &str,
+        context: &ProviderContext,
+    ) -> anyhow::Result<Option<Credentials>> {
+        let env_getter = context.env_getter;
+
+        if env_getter("AWS_EC2_METADATA_DISABLED").is_ok_and(|value| value == "true") {
+            return Ok(None);
+        }
+
+        return get_instance_credentials(env_getter);
+    }
+}
+
+fn get_instance_credentials(env_getter: &EnvGetter) -> anyhow::Result<Option<Credentials>> {
+    let endpoint = env_getter("AWS_EC2_METADATA_SERVICE_ENDPOINT")
+        .unwrap_or_else(|_| String::from(DEFAULT_ENDPOINT));
+    let endpoint = endpoint.trim_end_matches('/');
+
+    let timeout = match env_getter("AWS_METADATA_SERVICE_TIMEOUT") {
+        Ok(timeout) => timeout
+            .parse()
+            .context("AWS_METADATA_SERVICE_TIMEOUT must be a number of seconds")?,
+        Err(_) => 1,
+    };
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(timeout))
+        .build()
+        .context("Could not create the HTTP client")?;
+
+    // Outside of EC2 the link-local address does not answer, and neither does it from a container
+    // when the instance's hop limit is 1, so a failing token request means IMDS is not available.
+    let token = match client
+        .put(format!("{}/latest/api/token", endpoint))
+        .header("X-aws-ec2-metadata-token-ttl-seconds", TOKEN_TTL_SECONDS)
+        .send()
+    {
+        Ok(res) => read_success(res, "token")?,
+        Err(_) => return Ok(None),
+    };
+
+    let with_token = |request: RequestBuilder| {
+        return request.header("X-aws-ec2-metadata-token", &token);
+    };
+
+    let credentials_url = format!("{}/latest/meta-data/iam/security-credentials/", endpoint);
+
+    let res = with_token(client.get(&credentials_url))
+        .send()
+        .context("The instance role request failed")?;
+    let role_names = read_success(res, "instance role")?;
+    let role_name = role_names
+        .lines()
+        .next()
+        .ok_or_else(|| anyhow!("The instance has no IAM role attached"))?;
+
+    let res = with_token(client.get(format!("{}{}", credentials_url, role_name)))
+        .send()
+        .context("The instance credentials request failed")?;
+    let body: InstanceCredentialsResponse =
+        serde_json::from_str(&read_success(res, "credentials")?)
+            .context("Failed to deserialize the instance credentials")?;
+
+    if body.code != "Success" {
+        return Err(anyhow!(
+            "The instance metadata service returned {} for role {}",
+            body.code,
+            role_name
+        ));
+    }
+
+    let expiration = parse_expiration(Some(&body.expiration))?;
+
+    return Ok(Some(Credentials {
+        access_key_id: body.access_key_id,
+        secret_access_key: body.secret_access_key,
+        session_token: Some(body.token),
+        expiration,
+    }));
+}
+
+fn read_success(res: reqwest::blocking::Response, what: &str) -> anyhow::Result<String> {
+    let status = res.status();
+    let body = res
+        .text()
+        .with_context(|| format!("Could not read the instance metadata {}", what))?;
+
+    if !status.is_success() {
+        return Err(anyhow!(
+            "The instance metadata {} request responded with {}: {}",
+            what,
+            status,
+            body
+        ));
+    }
+
+    return Ok(body);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_server::serve;
+
+    #[test]
+    fn discovers_the_role_with_a_session_token() {
+        let (endpoint, requests) = serve(vec![
+            (200, "imds_token"),
+            (200, "bastion-role\n"),
+            (
+                200,
+                r#"{"Code": "Success", "AccessKeyId": "ASIA", "SecretAccessKey": "secret", "Token": "token", "Expiration": "2999-01-01T00:00:00Z"}"#,
+            ),
+        ]);
+
+        let env_getter: Box<EnvGetter> = Box::new(move |key| {
+            if key == "AWS_EC2_METADATA_SERVICE_ENDPOINT" {
+                return Ok(endpoint.clone());
+            }
+
+            return Err(anyhow!("not set"));
+        });
+
+        let credentials = get_instance_credentials(&env_getter).unwrap().unwrap();
+        assert_eq!("ASIA", credentials.access_key_id);
+
+        let requests = requests.join().unwrap();
+        assert_eq!(true, requests[0].starts_with("PUT /latest/api/token "));
+        assert_eq!(
+            true,
+            requests[0].contains("x-aws-ec2-metadata-token-ttl-seconds: 21600")
+        );
+        assert_eq!(
+            true,
+            requests[2].starts_with("GET /latest/meta-data/iam/security-credentials/bastion-role ")
+        );
+        assert_eq!(
+            true,
+            requests[2].contains("x-aws-ec2-metadata-token: imds_token")
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use std::io::{self, BufRead, IsTerminal, Write};
+
+use anyhow::{anyhow, Context};
+
+/// Returns the current code of the MFA device identified by the given `mfa_serial`.
+pub type MfaTokenProvider = dyn Fn(&str) -> anyhow::Result<String>;
+
+/// Asks for the MFA code on the terminal, refusing to block on a non-interactive stdin.
+pub fn prompt_mfa_code(serial_number: &str) -> anyhow::Result<String> {
+    let stdin = io::stdin();
+    if !stdin.is_terminal() {
+        return Err(anyhow!(
+            "The role requires MFA ({}) but stdin is not interactive, pass the code with --mfa-code",
+            serial_number
+        ));
+    }
+
+    eprint!("Enter MFA code for {}: ", serial_number);
+    io::stderr()
+        .flush()
+        .context("Could not write the MFA prompt")?;
+
+    let mut code = String::new();
+    stdin
+        .lock()
+        .read_line(&mut code)
+        .context("Could not read the MFA code")?;
+
+    return validate_mfa_code(code.trim());
+}
+
+pub fn validate_mfa_code(code: &str) -> anyhow::Result<String> {
+    if code.len() != 6 || !code.chars().all(|c| c.is_ascii_digit()) {
+        return Err(anyhow!("The MFA code must be 6 digits"));
+    }
+
+    return Ok(code.to_string());
+}
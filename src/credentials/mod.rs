@@ -0,0 +1,43 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+mod chain;
+mod config_profile;
+mod container;
+mod env;
+mod federation;
+mod imds;
+mod mfa;
+mod process;
+mod shared_file;
+mod sso;
+mod web_identity;
+
+pub use chain::{resolve_credentials, CredentialProvider, CredentialSource, ProviderContext};
+pub use federation::get_federated_credentials;
+pub use mfa::{prompt_mfa_code, validate_mfa_code, MfaTokenProvider};
+
+#[derive(Debug, Serialize)]
+pub struct Credentials {
+    #[serde(rename(serialize = "sessionId"))]
+    pub access_key_id: String,
+    #[serde(rename(serialize = "sessionKey"))]
+    pub secret_access_key: String,
+    #[serde(rename(serialize = "sessionToken"))]
+    pub session_token: Option<String>,
+    #[serde(skip_serializing)]
+    pub expiration: Option<DateTime<Utc>>,
+}
+
+/// Parses the RFC 3339 `Expiration` most credential sources report, when present.
+fn parse_expiration(expiration: Option<&str>) -> anyhow::Result<Option<DateTime<Utc>>> {
+    let expiration = expiration
+        .map(DateTime::parse_from_rfc3339)
+        .transpose()
+        .context("Invalid Expiration")?;
+
+    return Ok(expiration.map(|expiration| expiration.with_timezone(&Utc)));
+}
+
+pub type EnvGetter = dyn Fn(&str) -> anyhow::Result<String>;
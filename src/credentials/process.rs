@@ -0,0 +1,165 @@
+use std::process::Command;
+
+use anyhow::{anyhow, Context};
+use serde::Deserialize;
+
+use super::{
+    parse_expiration, shared_file::load_shared_credentials, CredentialProvider, Credentials,
+    ProviderContext,
+};
+use crate::{
+    config::{load_config, profile},
+    ini::Ini,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ProcessOutput {
+    version: u8,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+    expiration: Option<String>,
+}
+
+/// Runs the `credential_process` of the profile.
+pub struct ProcessProvider;
+
+impl CredentialProvider for ProcessProvider {
+    fn name(&self) -> &'static str {
+        return "process";
+    }
+
+    fn provide(
+        &self,
+        profile_name: &str,
+        context: &ProviderContext,
+    ) -> anyhow::Result<Option<Credentials>> {
+        let shared_credentials = load_shared_credentials(context.env_getter)?.unwrap_or_default();
+        let config = load_config(context.env_getter)?;
+
+        return match process_command(&shared_credentials, &config, profile_name) {
+            Some(command) => get_process_credentials(command).map(Some),
+            None => Ok(None),
+        };
+    }
+}
+
+/// Finds the `credential_process` of the profile, which the AWS CLI also accepts in the shared
+/// credentials file.
+pub(super) fn process_command<'a>(
+    shared_credentials: &'a Ini,
+    config: &'a Ini,
+    profile_name: &str,
+) -> Option<&'a String> {
+    return shared_credentials
+        .section(profile_name)
+        .into_iter()
+        .chain(profile(config, profile_name))
+        .find_map(|section| section.get("credential_process"));
+}
+
+/// Runs a profile's `credential_process` command and parses its standard JSON output.
+pub fn get_process_credentials(command: &str) -> anyhow::Result<Credentials> {
+    let output = shell(command)
+        .output()
+        .with_context(|| format!("Could not run the credential process `{}`", command))?;
+
+    if !output.status.success() {
+        return Err(anyhow!(
+            "The credential process `{}` exited with {}: {}",
+            command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    return parse_process_output(&output.stdout).with_context(|| {
+        format!(
+            "The credential process `{}` printed malformed JSON",
+            command
+        )
+    });
+}
+
+fn parse_process_output(stdout: &[u8]) -> anyhow::Result<Credentials> {
+    let output: ProcessOutput = serde_json::from_slice(stdout)?;
+
+    if output.version != 1 {
+        return Err(anyhow!("Unsupported Version {}", output.version));
+    }
+
+    let expiration = parse_expiration(output.expiration.as_deref())?;
+
+    return Ok(Credentials {
+        access_key_id: output.access_key_id,
+        secret_access_key: output.secret_access_key,
+        session_token: output.session_token,
+        expiration,
+    });
+}
+
+#[cfg(unix)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+
+    return shell;
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").arg(command);
+
+    return shell;
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_the_process_output() {
+        let credentials = parse_process_output(
+            br#"{"Version": 1, "AccessKeyId": "A", "SecretAccessKey": "B", "SessionToken": "C", "Expiration": "2999-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        assert_eq!("A", credentials.access_key_id);
+        assert_eq!("B", credentials.secret_access_key);
+        assert_eq!(Some(String::from("C")), credentials.session_token);
+        assert_eq!(
+            "2999-01-01 00:00:00 UTC",
+            credentials.expiration.unwrap().to_string()
+        );
+
+        assert_eq!(true, parse_process_output(b"not json").is_err());
+    }
+
+    #[test]
+    fn finds_the_command_in_the_credentials_file() {
+        let shared_credentials = Ini::parse("[dev]\ncredential_process = broker dev\n").unwrap();
+        let config = Ini::parse("[profile prod]\ncredential_process = broker prod\n").unwrap();
+
+        assert_eq!(
+            Some(&String::from("broker dev")),
+            process_command(&shared_credentials, &config, "dev")
+        );
+        assert_eq!(
+            Some(&String::from("broker prod")),
+            process_command(&shared_credentials, &config, "prod")
+        );
+        assert_eq!(None, process_command(&shared_credentials, &config, "qa"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn surfaces_a_failing_process() {
+        let result = get_process_credentials("echo broker unavailable >&2; exit 3");
+
+        let error_message = format!("{}", result.err().unwrap());
+        assert_eq!(true, error_message.contains("exit status: 3"));
+        assert_eq!(true, error_message.contains("broker unavailable"));
+    }
+}
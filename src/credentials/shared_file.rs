@@ -0,0 +1,112 @@
+use anyhow::anyhow;
+
+use super::{CredentialProvider, Credentials, EnvGetter, ProviderContext};
+use crate::{
+    config::{aws_file_path, load_config, profile, read_ini_file, Profile},
+    ini::Ini,
+};
+
+/// Uses the static keys of the profile from the shared credentials file, or from the config file.
+pub struct SharedFileProvider;
+
+impl CredentialProvider for SharedFileProvider {
+    fn name(&self) -> &'static str {
+        return "shared-file";
+    }
+
+    fn provide(
+        &self,
+        profile_name: &str,
+        context: &ProviderContext,
+    ) -> anyhow::Result<Option<Credentials>> {
+        let shared_credentials = load_shared_credentials(context.env_getter)?.unwrap_or_default();
+        let config = load_config(context.env_getter)?;
+
+        return match static_section(&shared_credentials, &config, profile_name) {
+            Some(section) => static_credentials(section, profile_name).map(Some),
+            None => Ok(None),
+        };
+    }
+}
+
+/// Finds the section holding the static keys of the profile, preferring the shared credentials file.
+///
+/// A section without an `aws_access_key_id` may configure e.g. a `credential_process` instead.
+pub(super) fn static_section<'a>(
+    shared_credentials: &'a Ini,
+    config: &'a Ini,
+    profile_name: &str,
+) -> Option<&'a Profile> {
+    return shared_credentials
+        .section(profile_name)
+        .into_iter()
+        .chain(profile(config, profile_name))
+        .find(|section| section.contains_key("aws_access_key_id"));
+}
+
+/// Loads the shared credentials file, `~/.aws/credentials` unless overridden with `AWS_SHARED_CREDENTIALS_FILE`.
+pub(super) fn load_shared_credentials(env_getter: &EnvGetter) -> anyhow::Result<Option<Ini>> {
+    let path = aws_file_path(env_getter, "AWS_SHARED_CREDENTIALS_FILE", "credentials")?;
+
+    return read_ini_file(&path);
+}
+
+/// Builds credentials out of the `aws_*` keys of a credentials or config file section.
+pub(super) fn static_credentials(
+    section: &Profile,
+    profile_name: &str,
+) -> anyhow::Result<Credentials> {
+    let get = |key: &str| {
+        return section
+            .get(key)
+            .cloned()
+            .ok_or_else(|| anyhow!("Profile {} is missing the {} property", profile_name, key));
+    };
+
+    return Ok(Credentials {
+        access_key_id: get("aws_access_key_id")?,
+        secret_access_key: get("aws_secret_access_key")?,
+        session_token: section.get("aws_session_token").cloned(),
+        expiration: None,
+    });
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn reads_the_named_profile() {
+        let ini = Ini::parse(
+            "[default]\naws_access_key_id = A\naws_secret_access_key = B\naws_session_token = C\n\n[dev]\naws_access_key_id = D\naws_secret_access_key = E\n",
+        )
+        .unwrap();
+
+        let credentials = static_credentials(ini.section("default").unwrap(), "default").unwrap();
+        assert_eq!("A", credentials.access_key_id);
+        assert_eq!("B", credentials.secret_access_key);
+        assert_eq!(Some(String::from("C")), credentials.session_token);
+
+        let credentials = static_credentials(ini.section("dev").unwrap(), "dev").unwrap();
+        assert_eq!("D", credentials.access_key_id);
+        assert_eq!(None, credentials.session_token);
+    }
+
+    #[test]
+    fn skips_sections_without_keys() {
+        let shared_credentials =
+            Ini::parse("[dev]\ncredential_process = broker dev\n\n[prod]\nregion = eu-west-1\n")
+                .unwrap();
+        let config =
+            Ini::parse("[profile prod]\naws_access_key_id = A\naws_secret_access_key = B\n")
+                .unwrap();
+
+        assert_eq!(None, static_section(&shared_credentials, &config, "dev"));
+        assert_eq!(
+            Some("A"),
+            static_section(&shared_credentials, &config, "prod")
+                .and_then(|section| section.get("aws_access_key_id"))
+                .map(String::as_str)
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::fs;
+
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sha1::{Digest, Sha1};
+
+use super::{CredentialProvider, Credentials, EnvGetter, ProviderContext};
+use crate::{
+    config::{aws_dir, load_config, profile, Profile},
+    ini::Ini,
+    sso::SsoClient,
+};
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CachedToken {
+    access_token: String,
+    expires_at: String,
+}
+
+/// Uses IAM Identity Center for profiles with a `sso_session` or `sso_start_url`.
+pub struct SsoProvider;
+
+impl CredentialProvider for SsoProvider {
+    fn name(&self) -> &'static str {
+        return "sso";
+    }
+
+    fn provide(
+        &self,
+        profile_name: &str,
+        context: &ProviderContext,
+    ) -> anyhow::Result<Option<Credentials>> {
+        let config = load_config(context.env_getter)?;
+
+        return match profile(&config, profile_name) {
+            Some(config_profile) if is_sso_profile(config_profile) => get_sso_credentials(
+                profile_name,
+                config_profile,
+                &config,
+                context.env_getter,
+                context.sso,
+            )
+            .map(Some),
+            _ => Ok(None),
+        };
+    }
+}
+
+pub fn is_sso_profile(config_profile: &Profile) -> bool {
+    return config_profile.contains_key("sso_session")
+        || config_profile.contains_key("sso_start_url");
+}
+
+/// Exchanges the cached IAM Identity Center token of a `sso_session` or legacy `sso_start_url`
+/// profile for the credentials of its `sso_account_id`/`sso_role_name`.
+pub fn get_sso_credentials(
+    profile_name: &str,
+    config_profile: &Profile,
+    config: &Ini,
+    env_getter: &EnvGetter,
+    sso: &SsoClient,
+) -> anyhow::Result<Credentials> {
+    let get = |section: &Profile, key: &str| {
+        return section
+            .get(key)
+            .cloned()
+            .ok_or_else(|| anyhow!("Profile {} is missing the {} property", profile_name, key));
+    };
+
+    // `sso_session` profiles share the token of their `[sso-session]` section, keyed by its name.
+    let (cache_key, sso_region) = match config_profile.get("sso_session") {
+        Some(session_name) => {
+            let session = config
+                .section(&format!("sso-session {}", session_name))
+                .ok_or_else(|| anyhow!("The sso-session {} is not defined", session_name))?;
+
+            (session_name.clone(), get(session, "sso_region")?)
+        }
+        None => (
+            get(config_profile, "sso_start_url")?,
+            get(config_profile, "sso_region")?,
+        ),
+    };
+
+    let cache_path = aws_dir(env_getter)?.join("sso").join("cache").join(format!(
+        "{}.json",
+        hex::encode(Sha1::digest(cache_key.as_bytes()))
+    ));
+
+    let contents = fs::read_to_string(&cache_path).with_context(|| {
+        format!(
+            "No cached SSO token found, run `aws sso login --profile {}`",
+            profile_name
+        )
+    })?;
+    let token: CachedToken = serde_json::from_str(&contents)
+        .with_context(|| format!("Malformed SSO token cache at {}", cache_path.display()))?;
+
+    let expires_at = DateTime::parse_from_rfc3339(&token.expires_at)
+        .with_context(|| format!("Malformed SSO token cache at {}", cache_path.display()))?;
+    if expires_at <= Utc::now() {
+        return Err(anyhow!(
+            "The cached SSO token has expired, run `aws sso login --profile {}`",
+            profile_name
+        ));
+    }
+
+    return sso.get_role_credentials(
+        &sso_region,
+        &token.access_token,
+        &get(config_profile, "sso_account_id")?,
+        &get(config_profile, "sso_role_name")?,
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use super::*;
+    use crate::test_server::serve_once;
+
+    #[test]
+    fn reads_the_token_cached_for_the_sso_session() {
+        let home = env::temp_dir().join(format!("aws-console-link-sso-{}", std::process::id()));
+        let cache_dir = home.join(".aws").join("sso").join("cache");
+        fs::create_dir_all(&cache_dir).unwrap();
+        // The SHA-1 of the `corp` session name.
+        fs::write(
+            cache_dir.join("ee0bfd2552fbd840c02cc48b6e823320543c450f.json"),
+            r#"{"accessToken": "cached_token", "expiresAt": "2999-01-01T00:00:00Z"}"#,
+        )
+        .unwrap();
+
+        let config = Ini::parse(
+            "[profile dev]\nsso_session = corp\nsso_account_id = 123456789012\nsso_role_name = Admin\n\n[sso-session corp]\nsso_start_url = https://corp.awsapps.com/start\nsso_region = eu-west-1\n",
+        )
+        .unwrap();
+        let (endpoint, request) = serve_once(
+            200,
+            r#"{"roleCredentials": {"accessKeyId": "ASIA", "secretAccessKey": "secret", "sessionToken": "token", "expiration": 32503680000000}}"#,
+        );
+
+        let home_path = home.to_string_lossy().to_string();
+        let env_getter: Box<EnvGetter> = Box::new(move |key| {
+            if key == "HOME" {
+                return Ok(home_path.clone());
+            }
+
+            return Err(anyhow!("not set"));
+        });
+
+        let credentials = get_sso_credentials(
+            "dev",
+            config.section("profile dev").unwrap(),
+            &config,
+            &env_getter,
+            &SsoClient::new(Some(endpoint)),
+        )
+        .unwrap();
+        assert_eq!("ASIA", credentials.access_key_id);
+        assert_eq!(
+            true,
+            request
+                .join()
+                .unwrap()
+                .contains("x-amz-sso_bearer_token: cached_token")
+        );
+
+        fs::remove_dir_all(home).unwrap();
+    }
+}
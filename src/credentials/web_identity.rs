@@ -0,0 +1,98 @@
+use std::fs;
+
+use anyhow::Context;
+use chrono::Utc;
+
+use super::{CredentialProvider, Credentials, ProviderContext};
+use crate::sts::StsClient;
+
+/// Uses the `AWS_WEB_IDENTITY_TOKEN_FILE` and `AWS_ROLE_ARN` variables set up by e.g. EKS IAM roles
+/// for service accounts.
+pub struct WebIdentityProvider;
+
+impl CredentialProvider for WebIdentityProvider {
+    fn name(&self) -> &'static str {
+        return "web-identity";
+    }
+
+    fn is_profile_agnostic(&self) -> bool {
+        return true;
+    }
+
+    fn provide(
+        &self,
+        _profile_name: &str,
+        context: &ProviderContext,
+    ) -> anyhow::Result<Option<Credentials>> {
+        let env_getter = context.env_getter;
+
+        let (token_file, role_arn) = match (
+            env_getter("AWS_WEB_IDENTITY_TOKEN_FILE"),
+            env_getter("AWS_ROLE_ARN"),
+        ) {
+            (Ok(token_file), Ok(role_arn)) => (token_file, role_arn),
+            _ => return Ok(None),
+        };
+
+        let role_session_name = env_getter("AWS_ROLE_SESSION_NAME")
+            .unwrap_or_else(|_| format!("aws-console-link-{}", Utc::now().timestamp()));
+
+        return get_web_identity_credentials(
+            &token_file,
+            &role_arn,
+            &role_session_name,
+            context.sts,
+        )
+        .map(Some);
+    }
+}
+
+pub fn get_web_identity_credentials(
+    token_file: &str,
+    role_arn: &str,
+    role_session_name: &str,
+    sts: &StsClient,
+) -> anyhow::Result<Credentials> {
+    let token = fs::read_to_string(token_file)
+        .with_context(|| format!("Could not read the web identity token at {}", token_file))?;
+
+    return sts.assume_role_with_web_identity(role_arn, role_session_name, token.trim());
+}
+
+#[cfg(test)]
+mod test {
+    use std::env;
+
+    use super::*;
+    use crate::test_server::serve_once;
+
+    #[test]
+    fn exchanges_the_token_without_signing() {
+        let token_file = env::temp_dir().join(format!(
+            "aws-console-link-web-identity-{}",
+            std::process::id()
+        ));
+        fs::write(&token_file, "oidc_token\n").unwrap();
+
+        let (endpoint, request) = serve_once(
+            200,
+            "<AssumeRoleWithWebIdentityResponse><AssumeRoleWithWebIdentityResult><Credentials><AccessKeyId>ASIA</AccessKeyId><SecretAccessKey>secret</SecretAccessKey><SessionToken>token</SessionToken></Credentials></AssumeRoleWithWebIdentityResult></AssumeRoleWithWebIdentityResponse>",
+        );
+
+        let credentials = get_web_identity_credentials(
+            &token_file.to_string_lossy(),
+            "arn:aws:iam::1:role/pod",
+            "pod",
+            &StsClient::new(Some(endpoint), "eu-west-1"),
+        )
+        .unwrap();
+        assert_eq!("ASIA", credentials.access_key_id);
+
+        let request = request.join().unwrap();
+        assert_eq!(true, request.contains("Action=AssumeRoleWithWebIdentity"));
+        assert_eq!(true, request.ends_with("WebIdentityToken=oidc_token"));
+        assert_eq!(false, request.contains("authorization:"));
+
+        fs::remove_file(token_file).unwrap();
+    }
+}
@@ -0,0 +1,197 @@
+use anyhow::{anyhow, Context};
+use url::{Host, Url};
+
+use crate::partition::Partition;
+
+/// Console paths of the services `--service` can deep-link to, a `#` starts the fragment read by the console app.
+const SERVICE_PATHS: &[(&str, &str)] = &[
+    ("cloudformation", "cloudformation/home"),
+    ("cloudwatch", "cloudwatch/home"),
+    ("dynamodb", "dynamodbv2/home"),
+    ("ec2", "ec2/home"),
+    ("ecs", "ecs/v2/home"),
+    ("iam", "iam/home"),
+    ("lambda", "lambda/home"),
+    ("logs", "cloudwatch/home#logsV2:log-groups"),
+    ("rds", "rds/home"),
+    ("s3", "s3/home"),
+    ("sns", "sns/v3/home"),
+    ("sqs", "sqs/v3/home"),
+    ("stepfunctions", "states/home"),
+];
+
+/// Builds the console URL of `path` in the partition, appending the `region` query parameter to any
+/// query the path already has and keeping its `#fragment` last.
+pub fn console_url(partition: Partition, region: &str, path: &str) -> String {
+    let (path, fragment) = match path.split_once('#') {
+        Some((path, fragment)) => (path, format!("#{}", fragment)),
+        None => (path, String::new()),
+    };
+    let (path, query) = match path.split_once('?') {
+        Some((path, query)) => (path, format!("{}&", query)),
+        None => (path, String::new()),
+    };
+
+    return format!(
+        "https://{}/{}?{}region={}{}",
+        partition.console_host(region),
+        path,
+        query,
+        region,
+        fragment
+    );
+}
+
+pub fn home_destination(partition: Partition, region: &str) -> String {
+    return console_url(partition, region, "console/home");
+}
+
+pub fn service_destination(
+    service: &str,
+    partition: Partition,
+    region: &str,
+) -> anyhow::Result<String> {
+    let path = SERVICE_PATHS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(service))
+        .map(|(_, path)| path)
+        .ok_or_else(|| {
+            let names = SERVICE_PATHS
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", ");
+
+            anyhow!("Unknown service {}, expected one of: {}", service, names)
+        })?;
+
+    return Ok(console_url(partition, region, path));
+}
+
+/// Resolves a `--destination`, joining relative paths to the regional console and only accepting
+/// absolute URLs on the console domain, so the login link cannot redirect to an arbitrary site.
+pub fn custom_destination(
+    destination: &str,
+    partition: Partition,
+    region: &str,
+) -> anyhow::Result<String> {
+    // A relative path may still carry a URL in its query or fragment, e.g. the SQS queue URL.
+    let url = match Url::parse(destination) {
+        Err(url::ParseError::RelativeUrlWithoutBase) => {
+            return Ok(console_url(
+                partition,
+                region,
+                destination.trim_start_matches('/'),
+            ));
+        }
+        url => url.with_context(|| format!("Invalid destination URL {}", destination))?,
+    };
+
+    let console_domain = partition.console_domain();
+
+    let is_console_host = match url.host() {
+        Some(Host::Domain(domain)) => {
+            domain == console_domain || domain.ends_with(&format!(".{}", console_domain))
+        }
+        _ => false,
+    };
+
+    if url.scheme() != "https" || !is_console_host {
+        return Err(anyhow!(
+            "The destination {} is not an https://*.{} URL",
+            destination,
+            console_domain
+        ));
+    }
+
+    return Ok(url.into());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn service_destination_keeps_the_fragment_last() {
+        assert_eq!(
+            "https://eu-west-1.console.aws.amazon.com/lambda/home?region=eu-west-1",
+            service_destination("lambda", Partition::Aws, "eu-west-1").unwrap()
+        );
+        assert_eq!(
+            "https://eu-west-1.console.aws.amazon.com/cloudwatch/home?region=eu-west-1#logsV2:log-groups",
+            service_destination("logs", Partition::Aws, "eu-west-1").unwrap()
+        );
+        assert_eq!(
+            true,
+            service_destination("nope", Partition::Aws, "eu-west-1").is_err()
+        );
+    }
+
+    #[test]
+    fn custom_destination_stays_on_the_console() {
+        assert_eq!(
+            "https://eu-west-1.console.aws.amazon.com/ec2/home?region=eu-west-1#Instances:",
+            custom_destination("/ec2/home#Instances:", Partition::Aws, "eu-west-1").unwrap()
+        );
+        assert_eq!(
+            "https://eu-west-1.console.aws.amazon.com/sqs/v3/home?region=eu-west-1#/queues/https://sqs.eu-west-1.amazonaws.com/1/jobs",
+            custom_destination(
+                "/sqs/v3/home#/queues/https://sqs.eu-west-1.amazonaws.com/1/jobs",
+                Partition::Aws,
+                "eu-west-1"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            "https://s3.console.aws.amazon.com/s3/buckets/my-bucket",
+            custom_destination(
+                "https://s3.console.aws.amazon.com/s3/buckets/my-bucket",
+                Partition::Aws,
+                "eu-west-1"
+            )
+            .unwrap()
+        );
+
+        assert_eq!(
+            true,
+            custom_destination(
+                "https://console.aws.amazon.com.evil.com/",
+                Partition::Aws,
+                "eu-west-1"
+            )
+            .is_err()
+        );
+        assert_eq!(
+            true,
+            custom_destination(
+                "https://evil.com/?console.aws.amazon.com",
+                Partition::Aws,
+                "eu-west-1"
+            )
+            .is_err()
+        );
+        assert_eq!(
+            true,
+            custom_destination(
+                "http://eu-west-1.console.aws.amazon.com/",
+                Partition::Aws,
+                "eu-west-1"
+            )
+            .is_err()
+        );
+
+        assert_eq!(
+            "https://console.amazonaws-us-gov.com/ec2/home?region=us-gov-west-1",
+            custom_destination("ec2/home", Partition::AwsUsGov, "us-gov-west-1").unwrap()
+        );
+        assert_eq!(
+            true,
+            custom_destination(
+                "https://eu-west-1.console.aws.amazon.com/",
+                Partition::AwsCn,
+                "cn-north-1"
+            )
+            .is_err()
+        );
+    }
+}
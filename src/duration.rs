@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Context};
+
+use crate::config::{profile_setting, Profile};
+
+/// The range of console session lengths the federation endpoint accepts.
+const SESSION_DURATION_RANGE: std::ops::RangeInclusive<u32> = 900..=43200;
+
+/// Parses a console session duration given in seconds or as e.g. `8h`, `90m` or `1h30m`.
+pub fn parse_session_duration(value: &str) -> anyhow::Result<u32> {
+    let seconds = parse_duration(value)?;
+
+    if !SESSION_DURATION_RANGE.contains(&seconds) {
+        return Err(anyhow!(
+            "The session duration must be between {}s and {}s (15m to 12h), got {}s",
+            SESSION_DURATION_RANGE.start(),
+            SESSION_DURATION_RANGE.end(),
+            seconds
+        ));
+    }
+
+    return Ok(seconds);
+}
+
+/// The console session length, `--session-duration` or the profile's `console_session_duration`.
+/// `None` leaves it to the federation endpoint.
+pub fn resolve_session_duration(
+    flag: Option<u32>,
+    config_profile: Option<&Profile>,
+    profile_name: &str,
+) -> anyhow::Result<Option<u32>> {
+    if flag.is_some() {
+        return Ok(flag);
+    }
+
+    return profile_setting(None, config_profile, "console_session_duration")
+        .map(parse_session_duration)
+        .transpose()
+        .with_context(|| {
+            format!(
+                "Invalid console_session_duration of profile {}",
+                profile_name
+            )
+        });
+}
+
+fn parse_duration(value: &str) -> anyhow::Result<u32> {
+    let invalid = || anyhow!("Invalid duration {}, expected e.g. 3600, 45m or 8h", value);
+
+    if let Ok(seconds) = value.parse() {
+        return Ok(seconds);
+    }
+
+    let mut seconds: u32 = 0;
+    let mut digits = String::new();
+    for c in value.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let amount: u32 = digits.parse().with_context(invalid)?;
+        seconds = amount
+            .checked_mul(unit)
+            .and_then(|amount| seconds.checked_add(amount))
+            .ok_or_else(invalid)?;
+        digits.clear();
+    }
+
+    if !digits.is_empty() || value.is_empty() {
+        return Err(invalid());
+    }
+
+    return Ok(seconds);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_session_durations() {
+        assert_eq!(28800, parse_session_duration("8h").unwrap());
+        assert_eq!(5400, parse_session_duration("1h30m").unwrap());
+        assert_eq!(900, parse_session_duration("900").unwrap());
+
+        assert_eq!(true, parse_session_duration("13h").is_err());
+        assert_eq!(true, parse_session_duration("5m").is_err());
+        assert_eq!(true, parse_session_duration("8 hours").is_err());
+        assert_eq!(true, parse_session_duration("1h30").is_err());
+    }
+
+    #[test]
+    fn parses_the_profile_session_duration() {
+        let config_profile =
+            Profile::from([(String::from("console_session_duration"), String::from("8h"))]);
+        let invalid_profile = Profile::from([(
+            String::from("console_session_duration"),
+            String::from("13h"),
+        )]);
+
+        assert_eq!(
+            Some(3600),
+            resolve_session_duration(Some(3600), Some(&invalid_profile), "dev").unwrap()
+        );
+        assert_eq!(
+            Some(28800),
+            resolve_session_duration(None, Some(&config_profile), "dev").unwrap()
+        );
+        assert_eq!(
+            true,
+            resolve_session_duration(None, Some(&invalid_profile), "dev").is_err()
+        );
+    }
+}
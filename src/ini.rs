@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+
+use anyhow::anyhow;
+
+/// A minimal parser for the INI dialect used by `~/.aws/credentials` and `~/.aws/config`.
+///
+/// Indented lines following a key with an empty value are treated as nested sub-properties
+/// (e.g. `s3 =\n  max_concurrent_requests = 10`) and are skipped, the tool never needs them.
+#[derive(Debug, Default)]
+pub struct Ini {
+    sections: HashMap<String, HashMap<String, String>>,
+}
+
+impl Ini {
+    pub fn parse(contents: &str) -> anyhow::Result<Ini> {
+        let mut sections: HashMap<String, HashMap<String, String>> = HashMap::new();
+        let mut current_section: Option<String> = None;
+
+        for (index, raw_line) in contents.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if raw_line.starts_with(char::is_whitespace) {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                let name = line
+                    .strip_prefix('[')
+                    .and_then(|rest| rest.strip_suffix(']'))
+                    .ok_or_else(|| anyhow!("Malformed section header on line {}", index + 1))?
+                    .trim()
+                    .to_string();
+
+                sections.entry(name.clone()).or_default();
+                current_section = Some(name);
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| anyhow!("Expected `key = value` on line {}", index + 1))?;
+
+            let section = current_section
+                .as_ref()
+                .ok_or_else(|| anyhow!("Property outside of a section on line {}", index + 1))?;
+
+            sections
+                .entry(section.clone())
+                .or_default()
+                .insert(key.trim().to_string(), value.trim().to_string());
+        }
+
+        return Ok(Ini { sections });
+    }
+
+    pub fn section(&self, name: &str) -> Option<&HashMap<String, String>> {
+        return self.sections.get(name);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_sections_and_skips_nested_properties() {
+        let ini = Ini::parse(
+            "# comment\n[default]\nregion = eu-west-1\ns3 =\n  max_concurrent_requests = 10\n\n[profile dev]\noutput=json\n",
+        )
+        .unwrap();
+
+        let default = ini.section("default").unwrap();
+        assert_eq!("eu-west-1", default["region"]);
+        assert_eq!("", default["s3"]);
+        assert_eq!(false, default.contains_key("max_concurrent_requests"));
+
+        assert_eq!("json", ini.section("profile dev").unwrap()["output"]);
+    }
+}
@@ -1,156 +1,367 @@
-use std::env::{self};
+#![allow(clippy::needless_return, clippy::bool_assert_comparison)]
+
+use std::{
+    env::{self},
+    path::PathBuf,
+    process,
+    time::Duration,
+};
 
 use anyhow::{anyhow, Context, Ok};
-use clap::Parser;
-use serde::{Deserialize, Serialize};
+use clap::{Parser, Subcommand};
+
+use crate::{
+    arn::Arn,
+    config::{load_config, profile, profile_setting},
+    credentials::{
+        get_federated_credentials, prompt_mfa_code, resolve_credentials, validate_mfa_code,
+        CredentialSource, EnvGetter, MfaTokenProvider, ProviderContext,
+    },
+    destination::{custom_destination, home_destination, service_destination},
+    duration::{parse_session_duration, resolve_session_duration},
+    output::{
+        container_url, copy_to_clipboard, has_display, open_in_browser, ContainerColor,
+        ContainerIcon,
+    },
+    partition::Partition,
+    region::{resolve_region, validate_region},
+    retry::Backoff,
+    signin::{get_console_url, get_switch_url, resolve_issuer, SigninClient, SigninError},
+    sso::SsoClient,
+    sts::StsClient,
+};
+
+mod arn;
+mod config;
+mod credentials;
+mod destination;
+mod duration;
+mod ini;
+mod output;
+mod partition;
+mod region;
+mod retry;
+mod signin;
+mod sigv4;
+mod sso;
+mod sts;
+#[cfg(test)]
+mod test_server;
 
 #[derive(Parser, Debug)]
 struct Args {
     profile_name: String,
 
+    /// Region of the console session, defaults to the region of the ARN passed to open-arn, AWS_REGION,
+    /// AWS_DEFAULT_REGION or the profile's region
     #[clap(short, long)]
-    region: String,
-}
+    region: Option<String>,
 
-fn main() -> anyhow::Result<()> {
-    let Args {
-        profile_name,
-        region,
-    } = Args::parse();
+    /// Partition of the region, derived from the region when omitted
+    #[clap(long, arg_enum)]
+    partition: Option<Partition>,
 
-    run(&profile_name, &region)?;
+    /// STS endpoint used to assume roles, defaults to AWS_ENDPOINT_URL_STS or the regional endpoint
+    #[clap(long)]
+    sts_endpoint: Option<String>,
 
-    return Ok(());
-}
+    /// Federation endpoint the sign-in token is requested from, defaults to AWS_CONSOLE_LINK_SIGNIN_ENDPOINT or the
+    /// regional endpoint
+    #[clap(long)]
+    signin_endpoint: Option<String>,
 
-fn run(profile_name: &str, region: &str) -> anyhow::Result<()> {
-    let env_getter: Box<EnvGetter> = Box::new(|key: &str| {
-        return env::var(key).map_err(anyhow::Error::msg);
-    });
+    /// Seconds to wait for the connection to the federation endpoint
+    #[clap(long, default_value_t = 5)]
+    connect_timeout: u64,
 
-    let credentials = get_aws_credentials(&profile_name, env_getter)?;
-    let signin_token = get_signin_token(&credentials, &region)?;
-    let console_url = get_console_url(&signin_token, &region)?;
+    /// Seconds to wait for each federation request to complete
+    #[clap(long, default_value_t = 30)]
+    timeout: u64,
 
-    open::that(console_url)?;
+    /// Times to retry the federation request after server errors, throttling or connection failures
+    #[clap(long, default_value_t = 3)]
+    retries: u32,
 
-    return Ok(());
-}
+    /// IAM Identity Center portal endpoint, defaults to AWS_ENDPOINT_URL_SSO or the endpoint of the profile's sso_region
+    #[clap(long)]
+    sso_endpoint: Option<String>,
 
-fn get_console_url(signin_token: &str, region: &str) -> anyhow::Result<String> {
-    let destination_url = format!(
-        "https://{}.console.aws.amazon.com/console/home?region={}",
-        region, region
-    );
+    /// IAM policy file scoping the console session of IAM users with long-term keys, allows everything by default
+    #[clap(long)]
+    federation_policy: Option<PathBuf>,
 
-    let url = format!("https://signin.aws.amazon.com/federation");
+    /// Duration in seconds of the federation token minted for IAM users with long-term keys
+    #[clap(long, value_parser = clap::value_parser!(u32).range(900..=129600))]
+    federation_duration: Option<u32>,
 
-    let url = reqwest::Url::parse_with_params(
-        &url,
-        &[
-            ("Action", "login"),
-            ("Issuer", "wojteks-app"),
-            ("Destination", &destination_url),
-            ("SigninToken", signin_token),
-        ],
-    )
-    .context("Failed to build the URL")?;
+    /// Length of the console session, e.g. 8h or 3600, between 15m and 12h, defaults to the profile's
+    /// console_session_duration
+    #[clap(long, value_parser = parse_session_duration)]
+    session_duration: Option<u32>,
 
-    return Ok(url.into());
-}
+    /// Current code of the profile's mfa_serial device, prompted for on the terminal when omitted
+    #[clap(long)]
+    mfa_code: Option<String>,
 
-#[derive(Debug, Deserialize)]
-struct GetSigninTokenResponse {
-    #[serde(alias = "SigninToken")]
-    signin_token: String,
-}
+    /// Credential sources to try, in order, instead of the default chain
+    #[clap(long, arg_enum, use_value_delimiter = true)]
+    credential_source: Vec<CredentialSource>,
 
-fn get_signin_token(credentials: &Credentials, region: &str) -> anyhow::Result<String> {
-    let serialized_credentials = serde_json::to_string_pretty(&credentials)
-        .context("Could not serialize the credentials")?;
+    /// Service whose console page to open instead of the console home, e.g. s3, lambda or dynamodb
+    #[clap(long)]
+    service: Option<String>,
 
-    let request_url = format!("https://{}.signin.aws.amazon.com/federation", region);
+    /// Console URL or path (e.g. /ec2/home#Instances:) to open instead of the console home
+    #[clap(long, conflicts_with = "service")]
+    destination: Option<String>,
 
-    let client = reqwest::blocking::Client::new();
-    let res = client
-        .get(request_url)
-        .query(&[
-            ("Action", "getSigninToken"),
-            ("Session", &serialized_credentials),
-        ])
-        .send()
-        .context("The request failed")?;
+    /// Print the console URL instead of opening it, the default when no display is available and no browser
+    /// command is configured
+    #[clap(long)]
+    print: bool,
 
-    if res.status().is_success() {
-        let body = res
-            .json::<GetSigninTokenResponse>()
-            .context("Failed to deserialize the response")?;
+    /// Copy the console URL to the clipboard of the local terminal (OSC 52), e.g. over SSH
+    #[clap(long, conflicts_with = "print")]
+    copy: bool,
 
-        return Ok(body.signin_token);
-    }
+    /// Browser command to open the console with instead of the default browser, e.g. "firefox -P {profile}",
+    /// defaults to the profile's console_browser
+    #[clap(long)]
+    browser: Option<String>,
+
+    /// URL the console returns to when the session expires, defaults to the profile's console_issuer
+    #[clap(long)]
+    issuer: Option<String>,
+
+    /// Sign out of the current console session before signing in, to switch accounts in the same browser
+    #[clap(long)]
+    switch: bool,
 
-    return Err(anyhow!("Request failed"));
+    /// Open the console in a Firefox container, named after the profile unless given as --container=NAME
+    #[clap(long, min_values = 0, max_values = 1, require_equals = true)]
+    container: Option<Option<String>>,
+
+    /// Color of the Firefox container
+    #[clap(long, arg_enum, requires = "container")]
+    container_color: Option<ContainerColor>,
+
+    /// Icon of the Firefox container
+    #[clap(long, arg_enum, requires = "container")]
+    container_icon: Option<ContainerIcon>,
+
+    /// Report which credential source was used
+    #[clap(short, long)]
+    verbose: bool,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
-#[derive(Debug, Serialize)]
-struct Credentials {
-    #[serde(rename(serialize = "sessionId"))]
-    access_key_id: String,
-    #[serde(rename(serialize = "sessionKey"))]
-    secret_access_key: String,
-    #[serde(rename(serialize = "sessionToken"))]
-    session_token: String,
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Open the console page of the resource identified by an ARN
+    OpenArn { arn: String },
 }
 
-type EnvGetter = dyn Fn(&str) -> anyhow::Result<String>;
+fn main() {
+    let args = Args::parse();
 
-fn get_aws_credentials(
-    profile_name: &str,
-    env_getter: Box<EnvGetter>,
-) -> anyhow::Result<Credentials> {
-    let exported_profile_name =
-        env_getter("AWS_PROFILE").context("Missing AWS_PROFILE variable")?;
+    if let Err(error) = run(&args) {
+        eprintln!("Error: {:?}", error);
 
-    if profile_name != exported_profile_name {
-        return Err(anyhow!(
-            "Request profile name different than the exported profile name"
-        ));
+        let exit_code = error
+            .downcast_ref::<SigninError>()
+            .map_or(1, SigninError::exit_code);
+        process::exit(exit_code);
     }
+}
 
-    let access_key_id =
-        env_getter("AWS_ACCESS_KEY_ID").context("Missing AWS_ACCESS_KEY_ID variable")?;
-
-    let secret_access_key =
-        env_getter("AWS_SECRET_ACCESS_KEY").context("Missing AWS_SECRET_ACCESS_KEY variable")?;
-
-    let session_token =
-        env_getter("AWS_SESSION_TOKEN").context("Missing AWS_SESSION_TOKEN variable")?;
+fn run(args: &Args) -> anyhow::Result<()> {
+    let Args {
+        profile_name,
+        region,
+        partition,
+        sts_endpoint,
+        signin_endpoint,
+        connect_timeout,
+        timeout,
+        retries,
+        sso_endpoint,
+        federation_policy,
+        federation_duration,
+        session_duration,
+        mfa_code,
+        credential_source,
+        service,
+        destination,
+        print,
+        copy,
+        browser,
+        issuer,
+        switch,
+        container,
+        container_color,
+        container_icon,
+        verbose,
+        command,
+    } = args;
+
+    let arn = match command {
+        Some(Command::OpenArn { arn }) => Some(Arn::parse(arn)?),
+        None => None,
+    };
 
-    return Ok(Credentials {
-        access_key_id,
-        secret_access_key,
-        session_token,
+    let env_getter: Box<EnvGetter> = Box::new(|key: &str| {
+        return env::var(key).map_err(anyhow::Error::msg);
     });
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    let region = resolve_region(
+        region.as_deref(),
+        arn.as_ref().and_then(Arn::region),
+        profile_name,
+        &env_getter,
+    )?;
+    validate_region(&region)?;
+    let region = region.as_str();
+
+    let region_partition = Partition::from_region(region);
+    let partition = partition.unwrap_or(region_partition);
+    if partition != region_partition {
+        return Err(anyhow!(
+            "The region {} is not in the {} partition",
+            region,
+            partition.name()
+        ));
+    }
 
-    #[test]
-    fn missing_aws_profile() {
-        let env_getter: Box<EnvGetter> = Box::new(|key| {
-            if key == "AWS_PROFILE" {
-                return Err(anyhow!("test_error"));
-            }
+    if let Some(arn) = &arn {
+        if arn.partition != partition.name() {
+            return Err(anyhow!(
+                "The ARN is in the {} partition but the region {} is in {}",
+                arn.partition,
+                region,
+                partition.name()
+            ));
+        }
+    }
+
+    if arn.is_some() && (service.is_some() || destination.is_some()) {
+        return Err(anyhow!(
+            "open-arn cannot be combined with --service or --destination"
+        ));
+    }
 
-            return Ok(String::from("foo"));
+    let destination_url = match (&arn, service, destination) {
+        (Some(arn), _, _) => arn.console_destination(partition, region)?,
+        (None, Some(service), _) => service_destination(service, partition, region)?,
+        (None, None, Some(destination)) => custom_destination(destination, partition, region)?,
+        (None, None, None) => home_destination(partition, region),
+    };
+
+    let sts_endpoint = sts_endpoint
+        .clone()
+        .or_else(|| env_getter("AWS_ENDPOINT_URL_STS").ok());
+    let sts = StsClient::new(sts_endpoint, region);
+
+    let sso_endpoint = sso_endpoint
+        .clone()
+        .or_else(|| env_getter("AWS_ENDPOINT_URL_SSO").ok());
+    let sso = SsoClient::new(sso_endpoint);
+
+    let mfa_code = mfa_code.clone();
+    let mfa_token_provider: Box<MfaTokenProvider> =
+        Box::new(move |serial_number: &str| match &mfa_code {
+            Some(code) => validate_mfa_code(code),
+            None => prompt_mfa_code(serial_number),
         });
 
-        let result = get_aws_credentials("test_profile", env_getter);
-        assert_eq!(true, result.is_err());
+    let config = load_config(&env_getter)?;
+    let config_profile = profile(&config, profile_name);
+
+    let session_duration =
+        resolve_session_duration(*session_duration, config_profile, profile_name)?;
+
+    let context = ProviderContext {
+        env_getter: &env_getter,
+        mfa_token_provider: &mfa_token_provider,
+        sts: &sts,
+        sso: &sso,
+        session_duration,
+    };
+    let credential_sources = if credential_source.is_empty() {
+        CredentialSource::DEFAULT_CHAIN
+    } else {
+        credential_source
+    };
+    let providers: Vec<_> = credential_sources
+        .iter()
+        .map(|source| source.provider())
+        .collect();
+
+    let credentials = resolve_credentials(profile_name, &providers, &context, *verbose)?;
+
+    // The console session of a federation token lasts as long as the token, the federation
+    // endpoint rejects a SessionDuration for it.
+    let (credentials, session_duration) = match credentials.session_token {
+        Some(_) => (credentials, session_duration),
+        None => (
+            get_federated_credentials(
+                &credentials,
+                federation_policy.as_deref(),
+                federation_duration.or(session_duration),
+                &sts,
+            )?,
+            None,
+        ),
+    };
+
+    let signin_endpoint = signin_endpoint
+        .clone()
+        .or_else(|| env_getter("AWS_CONSOLE_LINK_SIGNIN_ENDPOINT").ok());
+    let client = reqwest::blocking::Client::builder()
+        .connect_timeout(Duration::from_secs(*connect_timeout))
+        .timeout(Duration::from_secs(*timeout))
+        .build()
+        .context("Could not create the HTTP client")?;
+    let signin = SigninClient::new(
+        signin_endpoint,
+        partition,
+        region,
+        client,
+        Backoff::new(*retries),
+    );
 
-        let error_message = format!("{}", result.err().unwrap().source().unwrap());
-        assert_eq!("test_error", error_message)
+    let signin_token = signin.get_signin_token(&credentials, session_duration)?;
+    let issuer = resolve_issuer(issuer.as_deref(), config_profile);
+    let console_url = get_console_url(&signin_token, &destination_url, issuer, partition)?;
+    let console_url = if *switch {
+        get_switch_url(&console_url, partition)?
+    } else {
+        console_url
+    };
+    let console_url = match container {
+        Some(name) => container_url(
+            &console_url,
+            name.as_deref().unwrap_or(profile_name),
+            *container_color,
+            *container_icon,
+        ),
+        None => console_url,
+    };
+
+    let browser = profile_setting(browser.as_deref(), config_profile, "console_browser");
+
+    // An explicit browser command knows how to reach a browser, whatever the display detection says.
+    if *copy {
+        copy_to_clipboard(&console_url, &env_getter)?;
+    } else if *print || (browser.is_none() && !has_display(&env_getter)) {
+        println!("{}", console_url);
+    } else {
+        match browser {
+            Some(browser) => open_in_browser(browser, &console_url, profile_name)?,
+            None => open::that(console_url)?,
+        }
     }
+
+    return Ok(());
 }
@@ -0,0 +1,263 @@
+use std::{
+    io::{self, IsTerminal, Write},
+    process::Command,
+};
+
+use anyhow::{anyhow, Context};
+use clap::ArgEnum;
+use url::form_urlencoded;
+
+use crate::credentials::EnvGetter;
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContainerColor {
+    Blue,
+    Turquoise,
+    Green,
+    Yellow,
+    Orange,
+    Red,
+    Pink,
+    Purple,
+    Toolbar,
+}
+
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ContainerIcon {
+    Fingerprint,
+    Briefcase,
+    Dollar,
+    Cart,
+    Circle,
+    Gift,
+    Vacation,
+    Food,
+    Fruit,
+    Pet,
+    Tree,
+    Chill,
+    Fence,
+}
+
+/// screen drops DCS strings longer than its buffer, so the escape is passed through in chunks.
+const SCREEN_CHUNK_SIZE: usize = 76;
+
+/// Whether a browser can be opened, which on Linux and the BSDs needs an X11 or Wayland display and
+/// elsewhere rules out SSH sessions. WSL opens the Windows browser, with or without WSLg.
+pub fn has_display(env_getter: &EnvGetter) -> bool {
+    if cfg!(any(target_os = "macos", target_os = "windows")) {
+        return env_getter("SSH_CONNECTION").is_err() && env_getter("SSH_TTY").is_err();
+    }
+
+    if env_getter("WSL_DISTRO_NAME").is_ok() || env_getter("WSL_INTEROP").is_ok() {
+        return true;
+    }
+
+    return env_getter("DISPLAY").is_ok_and(|display| !display.is_empty())
+        || env_getter("WAYLAND_DISPLAY").is_ok_and(|display| !display.is_empty());
+}
+
+/// Wraps `url` in the `ext+container:` scheme handled by the "Open external links in a container"
+/// Firefox extension, which opens it in the named container and creates the container when missing.
+pub fn container_url(
+    url: &str,
+    name: &str,
+    color: Option<ContainerColor>,
+    icon: Option<ContainerIcon>,
+) -> String {
+    let mut params = form_urlencoded::Serializer::new(String::new());
+    params.append_pair("name", name).append_pair("url", url);
+
+    if let Some(color) = color.and_then(|color| color.to_possible_value()) {
+        params.append_pair("color", color.get_name());
+    }
+    if let Some(icon) = icon.and_then(|icon| icon.to_possible_value()) {
+        params.append_pair("icon", icon.get_name());
+    }
+
+    return format!("ext+container:{}", params.finish());
+}
+
+/// Opens `url` with a browser command template such as `firefox -P {profile}`, where `{url}` and
+/// `{profile}` are replaced in every argument and the URL is appended when the template has no `{url}`.
+pub fn open_in_browser(template: &str, url: &str, profile_name: &str) -> anyhow::Result<()> {
+    let command = browser_command(template, url, profile_name)?;
+
+    Command::new(&command[0])
+        .args(&command[1..])
+        .spawn()
+        .with_context(|| format!("Could not run the browser command {}", template))?;
+
+    return Ok(());
+}
+
+fn browser_command(template: &str, url: &str, profile_name: &str) -> anyhow::Result<Vec<String>> {
+    let mut command: Vec<String> = split_words(template)?
+        .iter()
+        .map(|word| {
+            word.replace("{profile}", profile_name)
+                .replace("{url}", url)
+        })
+        .collect();
+
+    if command.is_empty() {
+        return Err(anyhow!("The browser command is empty"));
+    }
+
+    if !template.contains("{url}") {
+        command.push(url.to_string());
+    }
+
+    return Ok(command);
+}
+
+/// Splits a command line on whitespace, keeping single and double quoted strings together.
+fn split_words(command: &str) -> anyhow::Result<Vec<String>> {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+
+    for c in command.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_with(String::new).push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+
+    if quote.is_some() {
+        return Err(anyhow!("Unterminated quote in {}", command));
+    }
+    words.extend(word);
+
+    return Ok(words);
+}
+
+/// Copies `text` to the clipboard of the local terminal with an OSC 52 escape, which also works
+/// over SSH. Prints the text instead when stdout is not a terminal.
+pub fn copy_to_clipboard(text: &str, env_getter: &EnvGetter) -> anyhow::Result<()> {
+    let mut stdout = io::stdout();
+    if !stdout.is_terminal() {
+        println!("{}", text);
+        return Ok(());
+    }
+
+    write!(stdout, "{}", osc52(text, env_getter))
+        .and_then(|_| stdout.flush())
+        .context("Could not write to the terminal")?;
+    eprintln!("Copied the console URL to the clipboard");
+
+    return Ok(());
+}
+
+/// Builds the OSC 52 escape, wrapped in a DCS passthrough when running inside tmux or screen
+/// since both swallow unknown escapes otherwise.
+fn osc52(text: &str, env_getter: &EnvGetter) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", base64::encode(text));
+
+    if env_getter("TMUX").is_ok() {
+        return format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"));
+    }
+
+    let is_screen = env_getter("STY").is_ok()
+        || env_getter("TERM").is_ok_and(|term| term.starts_with("screen"));
+    if is_screen {
+        return sequence
+            .as_bytes()
+            .chunks(SCREEN_CHUNK_SIZE)
+            .map(|chunk| format!("\x1bP{}\x1b\\", String::from_utf8_lossy(chunk)))
+            .collect();
+    }
+
+    return sequence;
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    fn requires_a_display_server() {
+        let headless: Box<EnvGetter> = Box::new(|_| Err(anyhow!("not set")));
+        let wayland: Box<EnvGetter> = Box::new(|key| {
+            return match key {
+                "WAYLAND_DISPLAY" => Ok(String::from("wayland-0")),
+                _ => Err(anyhow!("not set")),
+            };
+        });
+
+        let wsl: Box<EnvGetter> = Box::new(|key| {
+            return match key {
+                "WSL_DISTRO_NAME" => Ok(String::from("Ubuntu")),
+                _ => Err(anyhow!("not set")),
+            };
+        });
+
+        assert_eq!(false, has_display(&headless));
+        assert_eq!(true, has_display(&wayland));
+        assert_eq!(true, has_display(&wsl));
+    }
+
+    #[test]
+    fn fills_in_the_browser_template() {
+        assert_eq!(
+            vec!["firefox", "-P", "dev", "https://a?b&c"],
+            browser_command("firefox -P {profile}", "https://a?b&c", "dev").unwrap()
+        );
+        assert_eq!(
+            vec![
+                "chromium",
+                "--user-data-dir=/tmp/my profiles/dev",
+                "--app=https://a"
+            ],
+            browser_command(
+                "chromium '--user-data-dir=/tmp/my profiles/{profile}' --app={url}",
+                "https://a",
+                "dev"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            true,
+            browser_command("firefox \"-P", "https://a", "dev").is_err()
+        );
+    }
+
+    #[test]
+    fn wraps_the_url_for_a_container() {
+        assert_eq!(
+            "ext+container:name=prod+admin&url=https%3A%2F%2Fa%3Fb%26c&color=red&icon=fence",
+            container_url(
+                "https://a?b&c",
+                "prod admin",
+                Some(ContainerColor::Red),
+                Some(ContainerIcon::Fence)
+            )
+        );
+    }
+
+    #[test]
+    fn wraps_the_osc52_escape_for_tmux() {
+        let plain: Box<EnvGetter> = Box::new(|_| Err(anyhow!("not set")));
+        let tmux: Box<EnvGetter> = Box::new(|key| {
+            return match key {
+                "TMUX" => Ok(String::from("/tmp/tmux-1000/default,1,0")),
+                _ => Err(anyhow!("not set")),
+            };
+        });
+
+        assert_eq!("\x1b]52;c;aHR0cHM6Ly9h\x07", osc52("https://a", &plain));
+        assert_eq!(
+            "\x1bPtmux;\x1b\x1b]52;c;aHR0cHM6Ly9h\x07\x1b\\",
+            osc52("https://a", &tmux)
+        );
+    }
+}
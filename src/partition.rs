@@ -0,0 +1,140 @@
+use clap::ArgEnum;
+
+/// The isolated groups of AWS regions, each with its own sign-in and console domains.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Partition {
+    Aws,
+    AwsUsGov,
+    AwsCn,
+}
+
+impl Partition {
+    pub const ALL: &'static [Partition] = &[Partition::Aws, Partition::AwsUsGov, Partition::AwsCn];
+
+    pub fn from_region(region: &str) -> Partition {
+        if region.starts_with("us-gov-") {
+            return Partition::AwsUsGov;
+        }
+
+        if region.starts_with("cn-") {
+            return Partition::AwsCn;
+        }
+
+        return Partition::Aws;
+    }
+
+    /// The partition name used in ARNs.
+    pub fn name(self) -> &'static str {
+        return match self {
+            Partition::Aws => "aws",
+            Partition::AwsUsGov => "aws-us-gov",
+            Partition::AwsCn => "aws-cn",
+        };
+    }
+
+    pub fn console_domain(self) -> &'static str {
+        return match self {
+            Partition::Aws => "console.aws.amazon.com",
+            Partition::AwsUsGov => "console.amazonaws-us-gov.com",
+            Partition::AwsCn => "console.amazonaws.cn",
+        };
+    }
+
+    /// Only the commercial partition serves the console from regional subdomains.
+    pub fn console_host(self, region: &str) -> String {
+        return match self {
+            Partition::Aws => format!("{}.{}", region, self.console_domain()),
+            Partition::AwsUsGov | Partition::AwsCn => self.console_domain().to_string(),
+        };
+    }
+
+    /// The host of the federation endpoint the login URL points at.
+    pub fn signin_host(self) -> &'static str {
+        return match self {
+            Partition::Aws => "signin.aws.amazon.com",
+            Partition::AwsUsGov => "signin.amazonaws-us-gov.com",
+            Partition::AwsCn => "signin.amazonaws.cn",
+        };
+    }
+
+    /// The host of the federation endpoint `getSigninToken` is called on.
+    pub fn regional_signin_host(self, region: &str) -> String {
+        return match self {
+            Partition::Aws => format!("{}.{}", region, self.signin_host()),
+            Partition::AwsUsGov | Partition::AwsCn => self.signin_host().to_string(),
+        };
+    }
+
+    /// The regions known to be in the partition.
+    pub fn regions(self) -> &'static [&'static str] {
+        return match self {
+            Partition::Aws => &[
+                "af-south-1",
+                "ap-east-1",
+                "ap-east-2",
+                "ap-northeast-1",
+                "ap-northeast-2",
+                "ap-northeast-3",
+                "ap-south-1",
+                "ap-south-2",
+                "ap-southeast-1",
+                "ap-southeast-2",
+                "ap-southeast-3",
+                "ap-southeast-4",
+                "ap-southeast-5",
+                "ap-southeast-6",
+                "ap-southeast-7",
+                "ca-central-1",
+                "ca-west-1",
+                "eu-central-1",
+                "eu-central-2",
+                "eu-north-1",
+                "eu-south-1",
+                "eu-south-2",
+                "eu-west-1",
+                "eu-west-2",
+                "eu-west-3",
+                "il-central-1",
+                "me-central-1",
+                "me-south-1",
+                "mx-central-1",
+                "sa-east-1",
+                "us-east-1",
+                "us-east-2",
+                "us-west-1",
+                "us-west-2",
+            ],
+            Partition::AwsUsGov => &["us-gov-east-1", "us-gov-west-1"],
+            Partition::AwsCn => &["cn-north-1", "cn-northwest-1"],
+        };
+    }
+
+    /// The domain suffix of the service API endpoints, e.g. `sts.{region}.{dns_suffix}`.
+    pub fn dns_suffix(self) -> &'static str {
+        return match self {
+            Partition::Aws | Partition::AwsUsGov => "amazonaws.com",
+            Partition::AwsCn => "amazonaws.com.cn",
+        };
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn derives_the_partition_from_the_region() {
+        assert_eq!(Partition::Aws, Partition::from_region("eu-west-1"));
+        assert_eq!(Partition::AwsUsGov, Partition::from_region("us-gov-west-1"));
+        assert_eq!(Partition::AwsCn, Partition::from_region("cn-north-1"));
+
+        assert_eq!(
+            "eu-west-1.console.aws.amazon.com",
+            Partition::Aws.console_host("eu-west-1")
+        );
+        assert_eq!(
+            "signin.amazonaws-us-gov.com",
+            Partition::AwsUsGov.regional_signin_host("us-gov-west-1")
+        );
+    }
+}
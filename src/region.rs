@@ -0,0 +1,124 @@
+use anyhow::anyhow;
+
+use crate::{
+    config::{load_config, profile},
+    credentials::EnvGetter,
+    partition::Partition,
+};
+
+/// Picks the region of the console session from, in order, `--region`, the region of the ARN being
+/// opened, `AWS_REGION`, `AWS_DEFAULT_REGION` and the `region` of the profile in `~/.aws/config`.
+pub fn resolve_region(
+    region: Option<&str>,
+    arn_region: Option<&str>,
+    profile_name: &str,
+    env_getter: &EnvGetter,
+) -> anyhow::Result<String> {
+    if let Some(region) = region.or(arn_region) {
+        return Ok(region.to_string());
+    }
+
+    if let Ok(region) = env_getter("AWS_REGION").or_else(|_| env_getter("AWS_DEFAULT_REGION")) {
+        return Ok(region);
+    }
+
+    let config = load_config(env_getter)?;
+
+    return profile(&config, profile_name)
+        .and_then(|config_profile| config_profile.get("region"))
+        .cloned()
+        .ok_or_else(|| {
+            anyhow!(
+                "Missing the region, pass it with --region, set AWS_REGION or add a region to profile {}",
+                profile_name
+            )
+        });
+}
+
+/// Rejects regions missing from their partition's list, since the region ends up in hostnames.
+pub fn validate_region(region: &str) -> anyhow::Result<()> {
+    let partition = Partition::from_region(region);
+    if partition.regions().contains(&region) {
+        return Ok(());
+    }
+
+    let suggestion = Partition::ALL
+        .iter()
+        .flat_map(|partition| partition.regions())
+        .map(|known| (edit_distance(region, known), known))
+        .filter(|(distance, _)| *distance <= 3)
+        .min_by_key(|(distance, _)| *distance);
+
+    return match suggestion {
+        Some((_, known)) => Err(anyhow!(
+            "Unknown region {}, did you mean {}?",
+            region,
+            known
+        )),
+        None => Err(anyhow!(
+            "Unknown region {} in the {} partition",
+            region,
+            partition.name()
+        )),
+    };
+}
+
+/// The Levenshtein distance between two strings.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    return previous[b.len()];
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn resolves_the_region_in_order() {
+        let env_getter: Box<EnvGetter> = Box::new(|key| {
+            return match key {
+                "AWS_DEFAULT_REGION" => Ok(String::from("eu-central-1")),
+                _ => Err(anyhow!("not set")),
+            };
+        });
+
+        assert_eq!(
+            "us-east-1",
+            resolve_region(Some("us-east-1"), Some("eu-west-1"), "dev", &env_getter).unwrap()
+        );
+        assert_eq!(
+            "eu-west-1",
+            resolve_region(None, Some("eu-west-1"), "dev", &env_getter).unwrap()
+        );
+        assert_eq!(
+            "eu-central-1",
+            resolve_region(None, None, "dev", &env_getter).unwrap()
+        );
+    }
+
+    #[test]
+    fn suggests_the_closest_region() {
+        assert_eq!(true, validate_region("eu-west-1").is_ok());
+        assert_eq!(true, validate_region("us-gov-west-1").is_ok());
+
+        assert_eq!(
+            "Unknown region eu-wset-1, did you mean eu-west-1?",
+            validate_region("eu-wset-1").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "Unknown region mars.evil.com in the aws partition",
+            validate_region("mars.evil.com").unwrap_err().to_string()
+        );
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+/// Exponential backoff with jitter between the attempts of a retried request.
+#[derive(Clone, Copy, Debug)]
+pub struct Backoff {
+    pub retries: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Backoff {
+    pub fn new(retries: u32) -> Backoff {
+        return Backoff {
+            retries,
+            base_delay: Duration::from_millis(200),
+            max_delay: Duration::from_secs(5),
+        };
+    }
+
+    /// The delay before retry number `attempt` (from 0), a random point in the upper half of the
+    /// doubled delay so concurrent clients spread out without retrying too early.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+
+        let half = delay.as_millis() as u64 / 2;
+        let jitter = random() % (half + 1);
+
+        return Duration::from_millis(half + jitter);
+    }
+}
+
+/// A random number from the randomly seeded `RandomState`, good enough for jitter.
+fn random() -> u64 {
+    return RandomState::new().build_hasher().finish();
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn doubles_the_delay_up_to_the_cap() {
+        let backoff = Backoff::new(5);
+
+        for _ in 0..20 {
+            let first = backoff.delay(0);
+            assert_eq!(
+                true,
+                first >= Duration::from_millis(100) && first <= Duration::from_millis(200)
+            );
+
+            let third = backoff.delay(2);
+            assert_eq!(
+                true,
+                third >= Duration::from_millis(400) && third <= Duration::from_millis(800)
+            );
+
+            let capped = backoff.delay(30);
+            assert_eq!(
+                true,
+                capped >= Duration::from_millis(2500) && capped <= Duration::from_secs(5)
+            );
+        }
+    }
+}
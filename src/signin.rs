@@ -0,0 +1,434 @@
+use std::{fmt, thread};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::{
+    config::{profile_setting, Profile},
+    credentials::Credentials,
+    partition::Partition,
+    retry::Backoff,
+};
+
+const DEFAULT_ISSUER: &str = "aws-console-link";
+
+/// A minimal client for the AWS federation (sign-in) endpoint.
+pub struct SigninClient {
+    endpoint: String,
+    client: reqwest::blocking::Client,
+    backoff: Backoff,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetSigninTokenResponse {
+    #[serde(alias = "SigninToken")]
+    signin_token: String,
+}
+
+/// The ways getting a sign-in token fails, each exiting with its own code.
+#[derive(Debug)]
+pub enum SigninError {
+    MissingSessionToken,
+    CredentialsExpired(DateTime<Utc>),
+    Unreachable(reqwest::Error),
+    /// A 4xx response, the request or the credentials are at fault.
+    Rejected {
+        status: StatusCode,
+        body: String,
+        hint: Option<&'static str>,
+    },
+    /// A 5xx or 429 response, the request may succeed later.
+    Unavailable {
+        status: StatusCode,
+        body: String,
+    },
+    MalformedResponse(reqwest::Error),
+}
+
+impl SigninError {
+    /// Whether trying again may succeed, after a server error, throttling or a connection failure.
+    fn is_retryable(&self) -> bool {
+        return match self {
+            SigninError::Unavailable { .. } => true,
+            SigninError::Unreachable(error) => error.is_connect() || error.is_timeout(),
+            _ => false,
+        };
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        return match self {
+            SigninError::MissingSessionToken => 3,
+            SigninError::CredentialsExpired(_) => 4,
+            SigninError::Unreachable(_) => 5,
+            SigninError::Rejected { .. } => 6,
+            SigninError::Unavailable { .. } => 7,
+            SigninError::MalformedResponse(_) => 8,
+        };
+    }
+}
+
+impl fmt::Display for SigninError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            SigninError::MissingSessionToken => write!(
+                f,
+                "The federation endpoint requires temporary credentials with a session token"
+            ),
+            SigninError::CredentialsExpired(expiration) => write!(
+                f,
+                "The credentials expired at {}, refresh them (e.g. aws sso login) and try again",
+                expiration
+            ),
+            SigninError::Unreachable(_) => write!(
+                f,
+                "Could not reach the federation endpoint, check the network and that the region is enabled for the account"
+            ),
+            SigninError::Rejected { status, body, hint } => {
+                write!(f, "The federation endpoint responded with {}: {}", status, body)?;
+                if let Some(hint) = hint {
+                    write!(f, "\n{}", hint)?;
+                }
+                return Ok(());
+            }
+            SigninError::Unavailable { status, body } => write!(
+                f,
+                "The federation endpoint is unavailable, it responded with {}: {}",
+                status, body
+            ),
+            SigninError::MalformedResponse(_) => {
+                write!(f, "Failed to deserialize the federation response")
+            }
+        };
+    }
+}
+
+impl std::error::Error for SigninError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        return match self {
+            SigninError::Unreachable(error) | SigninError::MalformedResponse(error) => Some(error),
+            _ => None,
+        };
+    }
+}
+
+/// Guesses the cause of a rejected request, the endpoint answers most mistakes with a terse 400.
+fn rejection_hint(
+    status: StatusCode,
+    body: &str,
+    session_duration: Option<u32>,
+) -> Option<&'static str> {
+    let body = body.to_lowercase();
+
+    if body.contains("expired") {
+        return Some("The session token expired, refresh the credentials and try again");
+    }
+
+    if body.contains("region") || status == StatusCode::FORBIDDEN {
+        return Some("Check that the region is enabled for the account, or pass another --region");
+    }
+
+    if session_duration.is_some() && status == StatusCode::BAD_REQUEST {
+        return Some("The session duration may exceed what the role allows, or the role was assumed by another role (role chaining), try without --session-duration");
+    }
+
+    return None;
+}
+
+impl SigninClient {
+    /// Creates a client talking to `endpoint`, or to the federation endpoint of the region when none is given.
+    pub fn new(
+        endpoint: Option<String>,
+        partition: Partition,
+        region: &str,
+        client: reqwest::blocking::Client,
+        backoff: Backoff,
+    ) -> SigninClient {
+        let endpoint = endpoint.unwrap_or_else(|| {
+            format!(
+                "https://{}/federation",
+                partition.regional_signin_host(region)
+            )
+        });
+
+        return SigninClient {
+            endpoint,
+            client,
+            backoff,
+        };
+    }
+
+    /// Exchanges temporary credentials for a token the login URL signs in with, the console session
+    /// lasting `session_duration` seconds when given.
+    pub fn get_signin_token(
+        &self,
+        credentials: &Credentials,
+        session_duration: Option<u32>,
+    ) -> anyhow::Result<String> {
+        if credentials.session_token.is_none() {
+            return Err(SigninError::MissingSessionToken.into());
+        }
+
+        if let Some(expiration) = credentials.expiration {
+            if expiration <= Utc::now() {
+                return Err(SigninError::CredentialsExpired(expiration).into());
+            }
+        }
+
+        let serialized_credentials = serde_json::to_string_pretty(&credentials)
+            .context("Could not serialize the credentials")?;
+
+        let mut query = vec![
+            ("Action", String::from("getSigninToken")),
+            ("Session", serialized_credentials),
+        ];
+        if let Some(session_duration) = session_duration {
+            query.push(("SessionDuration", session_duration.to_string()));
+        }
+
+        let mut attempt = 0;
+        loop {
+            match self.request_signin_token(&query, session_duration) {
+                Err(error) if error.is_retryable() && attempt < self.backoff.retries => {
+                    let delay = self.backoff.delay(attempt);
+                    eprintln!("{}, retrying in {}ms", error, delay.as_millis());
+
+                    thread::sleep(delay);
+                    attempt += 1;
+                }
+                result => return result.map_err(anyhow::Error::from),
+            }
+        }
+    }
+
+    fn request_signin_token(
+        &self,
+        query: &[(&str, String)],
+        session_duration: Option<u32>,
+    ) -> Result<String, SigninError> {
+        let res = self
+            .client
+            .get(&self.endpoint)
+            .query(query)
+            .send()
+            // The URL carries the credentials, keep them out of the error message.
+            .map_err(|error| SigninError::Unreachable(error.without_url()))?;
+
+        let status = res.status();
+        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+            let body = res.text().unwrap_or_default();
+            return Err(SigninError::Unavailable { status, body });
+        }
+        if !status.is_success() {
+            let body = res.text().unwrap_or_default();
+            let hint = rejection_hint(status, &body, session_duration);
+            return Err(SigninError::Rejected { status, body, hint });
+        }
+
+        let body = res
+            .json::<GetSigninTokenResponse>()
+            .map_err(|error| SigninError::MalformedResponse(error.without_url()))?;
+
+        return Ok(body.signin_token);
+    }
+}
+
+/// The URL the console returns to when the session expires, `--issuer` or the profile's `console_issuer`.
+pub fn resolve_issuer<'a>(flag: Option<&'a str>, config_profile: Option<&'a Profile>) -> &'a str {
+    return profile_setting(flag, config_profile, "console_issuer").unwrap_or(DEFAULT_ISSUER);
+}
+
+pub fn get_console_url(
+    signin_token: &str,
+    destination_url: &str,
+    issuer: &str,
+    partition: Partition,
+) -> anyhow::Result<String> {
+    let url = format!("https://{}/federation", partition.signin_host());
+
+    let url = reqwest::Url::parse_with_params(
+        &url,
+        &[
+            ("Action", "login"),
+            ("Issuer", issuer),
+            ("Destination", destination_url),
+            ("SigninToken", signin_token),
+        ],
+    )
+    .context("Failed to build the URL")?;
+
+    return Ok(url.into());
+}
+
+/// Wraps the login URL in a sign out, since the console otherwise keeps the session of the
+/// previously opened account.
+pub fn get_switch_url(console_url: &str, partition: Partition) -> anyhow::Result<String> {
+    let url = format!("https://{}/oauth", partition.signin_host());
+
+    let url = reqwest::Url::parse_with_params(
+        &url,
+        &[("Action", "logout"), ("redirect_uri", console_url)],
+    )
+    .context("Failed to build the URL")?;
+
+    return Ok(url.into());
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_server::{serve, serve_once};
+
+    fn test_client(endpoint: String, retries: u32) -> SigninClient {
+        return SigninClient::new(
+            Some(endpoint),
+            Partition::Aws,
+            "eu-west-1",
+            reqwest::blocking::Client::new(),
+            Backoff {
+                retries,
+                base_delay: std::time::Duration::from_millis(1),
+                max_delay: std::time::Duration::from_millis(1),
+            },
+        );
+    }
+
+    fn session_credentials() -> Credentials {
+        return Credentials {
+            access_key_id: String::from("ASIA"),
+            secret_access_key: String::from("secret"),
+            session_token: Some(String::from("token")),
+            expiration: None,
+        };
+    }
+
+    #[test]
+    fn gets_the_signin_token() {
+        let (endpoint, request) = serve_once(200, r#"{"SigninToken": "signin_token"}"#);
+
+        let signin_token = test_client(endpoint, 0)
+            .get_signin_token(&session_credentials(), Some(28800))
+            .unwrap();
+        assert_eq!("signin_token", signin_token);
+
+        let request = request.join().unwrap();
+        assert_eq!(
+            true,
+            request.starts_with("GET /?Action=getSigninToken&Session=")
+        );
+        assert_eq!(true, request.contains("%22sessionToken%22%3A+%22token%22"));
+        assert_eq!(true, request.contains("&SessionDuration=28800 "));
+    }
+
+    #[test]
+    fn reports_failed_responses() {
+        let get_signin_token = |status, body, session_duration| {
+            let (endpoint, _) = serve_once(status, body);
+
+            let error = test_client(endpoint, 0)
+                .get_signin_token(&session_credentials(), session_duration)
+                .unwrap_err();
+            let exit_code = error.downcast_ref::<SigninError>().unwrap().exit_code();
+
+            return (exit_code, error.to_string());
+        };
+
+        assert_eq!(
+            (6, String::from("The federation endpoint responded with 400 Bad Request: Token expired\nThe session token expired, refresh the credentials and try again")),
+            get_signin_token(400, "Token expired", None)
+        );
+        assert_eq!(
+            true,
+            get_signin_token(400, "", Some(43200))
+                .1
+                .contains("try without --session-duration")
+        );
+        assert_eq!(
+            (7, String::from("The federation endpoint is unavailable, it responded with 503 Service Unavailable: ")),
+            get_signin_token(503, "", None)
+        );
+        assert_eq!(
+            (
+                8,
+                String::from("Failed to deserialize the federation response")
+            ),
+            get_signin_token(200, "<html>Sign in</html>", None)
+        );
+    }
+
+    #[test]
+    fn retries_unavailable_responses() {
+        let (endpoint, requests) = serve(vec![
+            (503, ""),
+            (429, "Rate exceeded"),
+            (200, r#"{"SigninToken": "signin_token"}"#),
+        ]);
+
+        let signin_token = test_client(endpoint, 2)
+            .get_signin_token(&session_credentials(), None)
+            .unwrap();
+        assert_eq!("signin_token", signin_token);
+        assert_eq!(3, requests.join().unwrap().len());
+
+        let (endpoint, requests) = serve(vec![(400, "Invalid session")]);
+        assert_eq!(
+            true,
+            test_client(endpoint, 2)
+                .get_signin_token(&session_credentials(), None)
+                .is_err()
+        );
+        assert_eq!(1, requests.join().unwrap().len());
+    }
+
+    #[test]
+    fn chains_the_logout_before_the_login() {
+        let console_url = get_console_url(
+            "signin_token",
+            "https://cn-north-1.console.amazonaws.cn/",
+            "aws-console-link",
+            Partition::AwsCn,
+        )
+        .unwrap();
+
+        assert_eq!(
+            "https://signin.amazonaws.cn/oauth?Action=logout&redirect_uri=https%3A%2F%2Fsignin.amazonaws.cn%2Ffederation%3FAction%3Dlogin%26Issuer%3Daws-console-link%26Destination%3Dhttps%253A%252F%252Fcn-north-1.console.amazonaws.cn%252F%26SigninToken%3Dsignin_token",
+            get_switch_url(&console_url, Partition::AwsCn).unwrap()
+        );
+
+        for (partition, logout_url) in [
+            (Partition::Aws, "https://signin.aws.amazon.com/oauth"),
+            (
+                Partition::AwsUsGov,
+                "https://signin.amazonaws-us-gov.com/oauth",
+            ),
+            (Partition::AwsCn, "https://signin.amazonaws.cn/oauth"),
+        ] {
+            let console_url = get_console_url(
+                "signin_token",
+                "https://console.example/",
+                "aws-console-link",
+                partition,
+            )
+            .unwrap();
+
+            let switch_url =
+                reqwest::Url::parse(&get_switch_url(&console_url, partition).unwrap()).unwrap();
+            assert_eq!(
+                logout_url,
+                format!(
+                    "{}{}",
+                    switch_url.origin().ascii_serialization(),
+                    switch_url.path()
+                )
+            );
+            assert_eq!(
+                vec![
+                    (String::from("Action"), String::from("logout")),
+                    (String::from("redirect_uri"), console_url),
+                ],
+                switch_url.query_pairs().into_owned().collect::<Vec<_>>()
+            );
+        }
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::credentials::Credentials;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// The parts of an HTTP request covered by an AWS Signature Version 4 signature.
+pub struct SignableRequest<'a> {
+    pub method: &'a str,
+    pub host: &'a str,
+    pub path: &'a str,
+    /// Already canonical (sorted and URI-encoded) query string.
+    pub query: &'a str,
+    /// Additional headers to sign, besides `host`, `x-amz-date` and `x-amz-security-token`.
+    pub headers: &'a [(&'a str, &'a str)],
+    pub payload: &'a [u8],
+}
+
+/// Computes the headers that have to be attached to `request` for AWS to accept it.
+pub fn sign(
+    request: &SignableRequest,
+    credentials: &Credentials,
+    region: &str,
+    service: &str,
+    now: DateTime<Utc>,
+) -> Vec<(String, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let mut headers: Vec<(String, String)> = request
+        .headers
+        .iter()
+        .map(|(name, value)| (name.to_lowercase(), value.trim().to_string()))
+        .collect();
+    headers.push((String::from("host"), request.host.to_string()));
+    headers.push((String::from("x-amz-date"), amz_date.clone()));
+    if let Some(session_token) = &credentials.session_token {
+        headers.push((String::from("x-amz-security-token"), session_token.clone()));
+    }
+    headers.sort();
+
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value))
+        .collect();
+    let signed_headers = headers
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(";");
+
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        request.method,
+        request.path,
+        request.query,
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(request.payload))
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes()))
+    );
+
+    let signing_key = [region, service, "aws4_request"].iter().fold(
+        hmac(
+            format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+            date.as_bytes(),
+        ),
+        |key, part| hmac(&key, part.as_bytes()),
+    );
+    let signature = hex::encode(hmac(&signing_key, string_to_sign.as_bytes()));
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+        credentials.access_key_id, scope, signed_headers, signature
+    );
+
+    let mut signature_headers = vec![
+        (String::from("Authorization"), authorization),
+        (String::from("X-Amz-Date"), amz_date),
+    ];
+    if let Some(session_token) = &credentials.session_token {
+        signature_headers.push((String::from("X-Amz-Security-Token"), session_token.clone()));
+    }
+
+    return signature_headers;
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = HmacSha256::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+
+    return mac.finalize().into_bytes().to_vec();
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    // The IAM `ListUsers` example from the AWS Signature Version 4 documentation.
+    #[test]
+    fn matches_the_documented_example() {
+        let credentials = Credentials {
+            access_key_id: String::from("AKIDEXAMPLE"),
+            secret_access_key: String::from("wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY"),
+            session_token: None,
+            expiration: None,
+        };
+
+        let headers = sign(
+            &SignableRequest {
+                method: "GET",
+                host: "iam.amazonaws.com",
+                path: "/",
+                query: "Action=ListUsers&Version=2010-05-08",
+                headers: &[(
+                    "Content-Type",
+                    "application/x-www-form-urlencoded; charset=utf-8",
+                )],
+                payload: b"",
+            },
+            &credentials,
+            "us-east-1",
+            "iam",
+            Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap(),
+        );
+
+        assert_eq!(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, SignedHeaders=content-type;host;x-amz-date, Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7",
+            headers[0].1
+        );
+    }
+}
@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Context};
+use chrono::{TimeZone, Utc};
+use serde::Deserialize;
+
+use crate::{credentials::Credentials, partition::Partition};
+
+/// A minimal client for the IAM Identity Center (SSO) portal API.
+pub struct SsoClient {
+    endpoint: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetRoleCredentialsResponse {
+    role_credentials: RoleCredentials,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RoleCredentials {
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: String,
+    /// Milliseconds since the epoch.
+    expiration: i64,
+}
+
+impl SsoClient {
+    /// Creates a client talking to `endpoint`, or to the portal of the profile's `sso_region` when none is given.
+    pub fn new(endpoint: Option<String>) -> SsoClient {
+        return SsoClient {
+            endpoint,
+            client: reqwest::blocking::Client::new(),
+        };
+    }
+
+    pub fn get_role_credentials(
+        &self,
+        sso_region: &str,
+        access_token: &str,
+        account_id: &str,
+        role_name: &str,
+    ) -> anyhow::Result<Credentials> {
+        let endpoint = self
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| portal_endpoint(sso_region));
+        let request_url = format!("{}/federation/credentials", endpoint.trim_end_matches('/'));
+
+        let res = self
+            .client
+            .get(request_url)
+            .query(&[("account_id", account_id), ("role_name", role_name)])
+            .header("x-amz-sso_bearer_token", access_token)
+            .send()
+            .context("The SSO request failed")?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let body = res.text().unwrap_or_default();
+            return Err(anyhow!("SSO responded with {}: {}", status, body));
+        }
+
+        let body = res
+            .json::<GetRoleCredentialsResponse>()
+            .context("Failed to deserialize the SSO response")?;
+        let role_credentials = body.role_credentials;
+
+        return Ok(Credentials {
+            access_key_id: role_credentials.access_key_id,
+            secret_access_key: role_credentials.secret_access_key,
+            session_token: Some(role_credentials.session_token),
+            expiration: Utc
+                .timestamp_millis_opt(role_credentials.expiration)
+                .single(),
+        });
+    }
+}
+
+/// The portal endpoint of the region, under the domain of its partition.
+fn portal_endpoint(sso_region: &str) -> String {
+    let partition = Partition::from_region(sso_region);
+
+    return format!(
+        "https://portal.sso.{}.{}",
+        sso_region,
+        partition.dns_suffix()
+    );
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_server::serve_once;
+
+    #[test]
+    fn get_role_credentials_sends_the_bearer_token() {
+        let (endpoint, request) = serve_once(
+            200,
+            r#"{"roleCredentials": {"accessKeyId": "ASIA", "secretAccessKey": "secret", "sessionToken": "token", "expiration": 32503680000000}}"#,
+        );
+
+        let credentials = SsoClient::new(Some(endpoint))
+            .get_role_credentials("eu-west-1", "access_token", "123456789012", "Admin")
+            .unwrap();
+        assert_eq!("ASIA", credentials.access_key_id);
+        assert_eq!(
+            "3000-01-01 00:00:00 UTC",
+            credentials.expiration.unwrap().to_string()
+        );
+
+        let request = request.join().unwrap();
+        assert_eq!(
+            true,
+            request.starts_with(
+                "GET /federation/credentials?account_id=123456789012&role_name=Admin "
+            )
+        );
+        assert_eq!(
+            true,
+            request.contains("x-amz-sso_bearer_token: access_token")
+        );
+
+        assert_eq!(
+            "https://portal.sso.eu-west-1.amazonaws.com",
+            portal_endpoint("eu-west-1")
+        );
+        assert_eq!(
+            "https://portal.sso.cn-north-1.amazonaws.com.cn",
+            portal_endpoint("cn-north-1")
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Utc};
+use url::{form_urlencoded, Url};
+
+use crate::{
+    credentials::Credentials,
+    partition::Partition,
+    sigv4::{sign, SignableRequest},
+};
+
+const STS_API_VERSION: &str = "2011-06-15";
+
+/// A minimal client for the AWS Security Token Service query API.
+pub struct StsClient {
+    endpoint: String,
+    region: String,
+    client: reqwest::blocking::Client,
+}
+
+pub struct AssumeRoleRequest<'a> {
+    pub role_arn: &'a str,
+    pub role_session_name: &'a str,
+    pub external_id: Option<&'a str>,
+    pub duration_seconds: Option<&'a str>,
+    /// The `mfa_serial` and the current code of the MFA device, for roles that require MFA.
+    pub mfa: Option<(&'a str, &'a str)>,
+}
+
+pub struct GetFederationTokenRequest<'a> {
+    pub name: &'a str,
+    pub policy: Option<&'a str>,
+    pub duration_seconds: Option<&'a str>,
+}
+
+impl StsClient {
+    /// Creates a client talking to `endpoint`, or to the regional STS endpoint when none is given.
+    pub fn new(endpoint: Option<String>, region: &str) -> StsClient {
+        let endpoint = endpoint.unwrap_or_else(|| {
+            let partition = Partition::from_region(region);
+            format!("https://sts.{}.{}/", region, partition.dns_suffix())
+        });
+
+        return StsClient {
+            endpoint,
+            region: region.to_string(),
+            client: reqwest::blocking::Client::new(),
+        };
+    }
+
+    pub fn assume_role(
+        &self,
+        credentials: &Credentials,
+        request: &AssumeRoleRequest,
+    ) -> anyhow::Result<Credentials> {
+        let mut params = vec![
+            ("RoleArn", request.role_arn),
+            ("RoleSessionName", request.role_session_name),
+        ];
+        if let Some(external_id) = request.external_id {
+            params.push(("ExternalId", external_id));
+        }
+        if let Some(duration_seconds) = request.duration_seconds {
+            params.push(("DurationSeconds", duration_seconds));
+        }
+        if let Some((serial_number, token_code)) = request.mfa {
+            params.push(("SerialNumber", serial_number));
+            params.push(("TokenCode", token_code));
+        }
+
+        let body = self
+            .call(Some(credentials), "AssumeRole", &params)
+            .with_context(|| format!("Could not assume the {} role", request.role_arn))?;
+
+        return parse_credentials(&body);
+    }
+
+    /// Exchanges an OIDC token (e.g. an EKS service account token) for the credentials of `role_arn`.
+    pub fn assume_role_with_web_identity(
+        &self,
+        role_arn: &str,
+        role_session_name: &str,
+        web_identity_token: &str,
+    ) -> anyhow::Result<Credentials> {
+        let params = [
+            ("RoleArn", role_arn),
+            ("RoleSessionName", role_session_name),
+            ("WebIdentityToken", web_identity_token),
+        ];
+
+        let body = self
+            .call(None, "AssumeRoleWithWebIdentity", &params)
+            .with_context(|| format!("Could not assume the {} role", role_arn))?;
+
+        return parse_credentials(&body);
+    }
+
+    /// Mints session credentials for an IAM user that only has long-term access keys.
+    pub fn get_federation_token(
+        &self,
+        credentials: &Credentials,
+        request: &GetFederationTokenRequest,
+    ) -> anyhow::Result<Credentials> {
+        let mut params = vec![("Name", request.name)];
+        if let Some(policy) = request.policy {
+            params.push(("Policy", policy));
+        }
+        if let Some(duration_seconds) = request.duration_seconds {
+            params.push(("DurationSeconds", duration_seconds));
+        }
+
+        let body = self
+            .call(Some(credentials), "GetFederationToken", &params)
+            .context("Could not get a federation token")?;
+
+        return parse_credentials(&body);
+    }
+
+    /// Sends an STS query, signed with `credentials` unless the action is unauthenticated.
+    fn call(
+        &self,
+        credentials: Option<&Credentials>,
+        action: &str,
+        params: &[(&str, &str)],
+    ) -> anyhow::Result<String> {
+        let url = Url::parse(&self.endpoint).context("Invalid STS endpoint")?;
+        let host = match (url.host_str(), url.port()) {
+            (Some(host), Some(port)) => format!("{}:{}", host, port),
+            (Some(host), None) => host.to_string(),
+            _ => return Err(anyhow!("The STS endpoint is missing a host")),
+        };
+
+        let payload = form_urlencoded::Serializer::new(String::new())
+            .append_pair("Action", action)
+            .append_pair("Version", STS_API_VERSION)
+            .extend_pairs(params)
+            .finish();
+
+        let content_type = "application/x-www-form-urlencoded; charset=utf-8";
+        let signature_headers = match credentials {
+            Some(credentials) => sign(
+                &SignableRequest {
+                    method: "POST",
+                    host: &host,
+                    path: url.path(),
+                    query: "",
+                    headers: &[("Content-Type", content_type)],
+                    payload: payload.as_bytes(),
+                },
+                credentials,
+                &self.region,
+                "sts",
+                Utc::now(),
+            ),
+            None => vec![],
+        };
+
+        let mut request = self
+            .client
+            .post(url)
+            .header("Content-Type", content_type)
+            .body(payload);
+        for (name, value) in signature_headers {
+            request = request.header(name, value);
+        }
+
+        let res = request.send().context("The STS request failed")?;
+        let status = res.status();
+        let body = res.text().context("Could not read the STS response")?;
+
+        if !status.is_success() {
+            let message = xml_value(&body, "Message").unwrap_or(body);
+            return Err(anyhow!("STS responded with {}: {}", status, message));
+        }
+
+        return Ok(body);
+    }
+}
+
+fn parse_credentials(body: &str) -> anyhow::Result<Credentials> {
+    let get = |tag: &str| {
+        return xml_value(body, tag)
+            .ok_or_else(|| anyhow!("The STS response is missing the {} element", tag));
+    };
+
+    return Ok(Credentials {
+        access_key_id: get("AccessKeyId")?,
+        secret_access_key: get("SecretAccessKey")?,
+        session_token: Some(get("SessionToken")?),
+        expiration: xml_value(body, "Expiration")
+            .and_then(|expiration| DateTime::parse_from_rfc3339(&expiration).ok())
+            .map(|expiration| expiration.with_timezone(&Utc)),
+    });
+}
+
+/// Extracts the text of the first `<tag>` element, enough for the flat STS responses.
+fn xml_value(body: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+
+    let start = body.find(&open)? + open.len();
+    let end = start + body[start..].find(&close)?;
+
+    let value = body[start..end]
+        .trim()
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&");
+
+    return Some(value);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_server::serve_once;
+
+    #[test]
+    fn assume_role_returns_the_temporary_credentials() {
+        let (endpoint, request) = serve_once(
+            200,
+            "<AssumeRoleResponse><AssumeRoleResult><Credentials><AccessKeyId>ASIA</AccessKeyId><SecretAccessKey>secret</SecretAccessKey><SessionToken>token</SessionToken></Credentials></AssumeRoleResult></AssumeRoleResponse>",
+        );
+
+        let source = Credentials {
+            access_key_id: String::from("AKIA"),
+            secret_access_key: String::from("source_secret"),
+            session_token: None,
+            expiration: None,
+        };
+        let credentials = StsClient::new(Some(endpoint), "eu-west-1")
+            .assume_role(
+                &source,
+                &AssumeRoleRequest {
+                    role_arn: "arn:aws:iam::123456789012:role/admin",
+                    role_session_name: "test",
+                    external_id: None,
+                    duration_seconds: None,
+                    mfa: None,
+                },
+            )
+            .unwrap();
+
+        assert_eq!("ASIA", credentials.access_key_id);
+        assert_eq!("secret", credentials.secret_access_key);
+        assert_eq!(Some(String::from("token")), credentials.session_token);
+
+        let request = request.join().unwrap();
+        assert_eq!(true, request.contains("Action=AssumeRole"));
+        assert_eq!(
+            true,
+            request.contains("RoleArn=arn%3Aaws%3Aiam%3A%3A123456789012%3Arole%2Fadmin")
+        );
+        assert_eq!(
+            true,
+            request.contains("Credential=AKIA/") && request.contains("/eu-west-1/sts/aws4_request")
+        );
+    }
+
+    #[test]
+    fn surfaces_the_sts_error_message() {
+        let (endpoint, _) = serve_once(
+            403,
+            "<ErrorResponse><Error><Code>AccessDenied</Code><Message>Not authorized</Message></Error></ErrorResponse>",
+        );
+
+        let source = Credentials {
+            access_key_id: String::from("AKIA"),
+            secret_access_key: String::from("source_secret"),
+            session_token: None,
+            expiration: None,
+        };
+        let result = StsClient::new(Some(endpoint), "eu-west-1").assume_role(
+            &source,
+            &AssumeRoleRequest {
+                role_arn: "arn:aws:iam::123456789012:role/admin",
+                role_session_name: "test",
+                external_id: None,
+                duration_seconds: None,
+                mfa: None,
+            },
+        );
+
+        let error_message = format!("{:#}", result.err().unwrap());
+        assert_eq!(true, error_message.contains("Not authorized"));
+    }
+}
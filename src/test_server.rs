@@ -0,0 +1,69 @@
+//! A tiny HTTP stub used by the tests in place of the real AWS endpoints.
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    thread::{self, JoinHandle},
+};
+
+/// Answers the next `responses.len()` connections in order, one canned `(status, body)` each.
+///
+/// The handle yields the raw requests (head and body) that were received.
+pub fn serve(responses: Vec<(u16, &'static str)>) -> (String, JoinHandle<Vec<String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}/", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let mut requests = vec![];
+
+        for (status, body) in responses {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut request = String::new();
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some((name, value)) = line.split_once(':') {
+                    if name.eq_ignore_ascii_case("content-length") {
+                        content_length = value.trim().parse().unwrap();
+                    }
+                }
+                request.push_str(&line);
+                if line == "\r\n" || line.is_empty() {
+                    break;
+                }
+            }
+
+            let mut request_body = vec![0; content_length];
+            reader.read_exact(&mut request_body).unwrap();
+            request.push_str(&String::from_utf8_lossy(&request_body));
+            requests.push(request);
+
+            write!(
+                stream,
+                "HTTP/1.1 {} STUB\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+
+        return requests;
+    });
+
+    return (endpoint, handle);
+}
+
+/// Answers a single request, yielding the raw request that was received.
+pub fn serve_once(status: u16, body: &'static str) -> (String, JoinHandle<String>) {
+    let (endpoint, handle) = serve(vec![(status, body)]);
+
+    let handle = thread::spawn(move || {
+        return handle.join().unwrap().remove(0);
+    });
+
+    return (endpoint, handle);
+}
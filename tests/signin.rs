@@ -0,0 +1,72 @@
+#![allow(clippy::needless_return, clippy::bool_assert_comparison)]
+
+//! Runs the binary against a stub federation endpoint, as set with `AWS_CONSOLE_LINK_SIGNIN_ENDPOINT`
+//! or `--signin-endpoint`.
+
+use std::{
+    env,
+    process::{Command, Output},
+};
+
+#[path = "../src/test_server.rs"]
+mod test_server;
+
+use test_server::serve_once;
+
+fn aws_console_link(signin_endpoint: &str, args: &[&str]) -> Output {
+    return Command::new(env!("CARGO_BIN_EXE_aws-console-link"))
+        .env_clear()
+        .env("HOME", env::temp_dir().join("aws-console-link-no-home"))
+        .env("AWS_PROFILE", "dev")
+        .env("AWS_ACCESS_KEY_ID", "ASIA")
+        .env("AWS_SECRET_ACCESS_KEY", "secret")
+        .env("AWS_SESSION_TOKEN", "token")
+        .env("AWS_CONSOLE_LINK_SIGNIN_ENDPOINT", signin_endpoint)
+        .args(["dev", "--region", "eu-west-1", "--print", "--retries", "0"])
+        .args(args)
+        .output()
+        .unwrap();
+}
+
+#[test]
+fn prints_the_console_url() {
+    let (endpoint, request) = serve_once(200, r#"{"SigninToken": "signin_token"}"#);
+
+    let output = aws_console_link(&endpoint, &[]);
+    assert_eq!(true, output.status.success());
+    assert_eq!(
+        "https://signin.aws.amazon.com/federation?Action=login&Issuer=aws-console-link&Destination=https%3A%2F%2Feu-west-1.console.aws.amazon.com%2Fconsole%2Fhome%3Fregion%3Deu-west-1&SigninToken=signin_token\n",
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    let request = request.join().unwrap();
+    assert_eq!(
+        true,
+        request.starts_with("GET /?Action=getSigninToken&Session=")
+    );
+    assert_eq!(true, request.contains("%22sessionToken%22%3A+%22token%22"));
+}
+
+#[test]
+fn prefers_the_signin_endpoint_flag() {
+    let (endpoint, _) = serve_once(200, r#"{"SigninToken": "signin_token"}"#);
+
+    let output = aws_console_link("http://127.0.0.1:1/", &["--signin-endpoint", &endpoint]);
+    assert_eq!(true, output.status.success());
+}
+
+#[test]
+fn exits_with_the_code_of_the_failure() {
+    let exit_code = |status, body| {
+        let (endpoint, _) = serve_once(status, body);
+
+        let output = aws_console_link(&endpoint, &[]);
+        assert_eq!(true, output.stdout.is_empty());
+
+        return output.status.code();
+    };
+
+    assert_eq!(Some(6), exit_code(400, "Token expired"));
+    assert_eq!(Some(7), exit_code(503, ""));
+    assert_eq!(Some(8), exit_code(200, "<html>Sign in</html>"));
+}